@@ -3,26 +3,30 @@
 //! This module implements "nerf-darting" - npm's mechanism for scoping
 //! credentials to specific registries to prevent credential leakage.
 
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::secret_string::{wipe, SecretString};
+use crate::token::TokenInfo;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use std::fmt;
 use std::path::PathBuf;
+use std::str::FromStr;
 use url::Url;
 
 /// Credentials for authenticating with an npm registry.
 ///
 /// # Security Notes
 ///
-/// - The `Debug` implementation redacts sensitive fields (tokens, passwords)
-///   to prevent accidental credential leakage in logs or error messages.
+/// - Tokens and passwords are held in [`SecretString`], so the `Debug`
+///   output redacts them and, with the `zeroize` feature, their memory is
+///   wiped on drop.
 /// - `PartialEq` is intentionally not implemented to prevent timing attacks
 ///   when comparing credentials.
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub enum Credentials {
     /// Bearer token authentication (`_authToken`).
     /// This is the recommended authentication method.
     Token {
-        token: String,
+        token: SecretString,
         /// Optional client certificate for mTLS.
         cert: Option<ClientCert>,
     },
@@ -31,7 +35,7 @@ pub enum Credentials {
     /// The password is decoded from base64 `_password` field.
     BasicAuth {
         username: String,
-        password: String,
+        password: SecretString,
         /// Optional client certificate for mTLS.
         cert: Option<ClientCert>,
     },
@@ -39,11 +43,11 @@ pub enum Credentials {
     /// Legacy `_auth` field containing base64-encoded `username:password`.
     LegacyAuth {
         /// The raw base64-encoded auth string.
-        auth: String,
+        auth: SecretString,
         /// Decoded username.
         username: String,
         /// Decoded password.
-        password: String,
+        password: SecretString,
         /// Optional client certificate for mTLS.
         cert: Option<ClientCert>,
     },
@@ -52,30 +56,74 @@ pub enum Credentials {
     ClientCertOnly(ClientCert),
 }
 
-impl fmt::Debug for Credentials {
+/// The kind of [`Credentials`] configured for a registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CredentialKind {
+    /// Bearer token (`_authToken`).
+    Token,
+    /// Username and base64 `_password`.
+    BasicAuth,
+    /// Legacy base64 `_auth`.
+    LegacyAuth,
+    /// Client certificate only (`certfile`/`keyfile`).
+    ClientCert,
+}
+
+impl CredentialKind {
+    /// Get a short lowercase name for display.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CredentialKind::Token => "token",
+            CredentialKind::BasicAuth => "basic",
+            CredentialKind::LegacyAuth => "legacy",
+            CredentialKind::ClientCert => "client-cert",
+        }
+    }
+}
+
+impl fmt::Display for CredentialKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Login flow for `npm login` and 2FA prompts (npm's `auth-type` setting).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AuthType {
+    /// Browser-based login (npm's default).
+    #[default]
+    Web,
+    /// Username, password and one-time password prompted on the terminal.
+    Legacy,
+}
+
+impl AuthType {
+    /// Get the npm name of the auth type.
+    pub fn as_str(&self) -> &'static str {
         match self {
-            Credentials::Token { cert, .. } => f
-                .debug_struct("Token")
-                .field("token", &"[REDACTED]")
-                .field("cert", cert)
-                .finish(),
-            Credentials::BasicAuth { username, cert, .. } => f
-                .debug_struct("BasicAuth")
-                .field("username", username)
-                .field("password", &"[REDACTED]")
-                .field("cert", cert)
-                .finish(),
-            Credentials::LegacyAuth { username, cert, .. } => f
-                .debug_struct("LegacyAuth")
-                .field("auth", &"[REDACTED]")
-                .field("username", username)
-                .field("password", &"[REDACTED]")
-                .field("cert", cert)
-                .finish(),
-            Credentials::ClientCertOnly(cert) => {
-                f.debug_tuple("ClientCertOnly").field(cert).finish()
-            }
+            AuthType::Web => "web",
+            AuthType::Legacy => "legacy",
+        }
+    }
+}
+
+impl fmt::Display for AuthType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AuthType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "web" => Ok(AuthType::Web),
+            "legacy" => Ok(AuthType::Legacy),
+            _ => Err(Error::InvalidConfigValue {
+                key: "auth-type".to_string(),
+                value: s.to_string(),
+            }),
         }
     }
 }
@@ -132,6 +180,38 @@ pub fn nerf_dart(url: &Url) -> String {
     format!("//{}{}{}", host, port, normalized_path)
 }
 
+/// Config fields that carry registry credentials.
+const CREDENTIAL_FIELDS: &[&str] = &["_authToken", "_auth", "_password", "username"];
+
+/// Check whether a config key holds registry credentials.
+///
+/// Matches both nerf-darted keys (`//registry.npmjs.org/:_authToken`) and
+/// bare top-level keys (`_authToken`).
+///
+/// # Examples
+///
+/// ```
+/// use npmrc_config_rs::is_credential_key;
+///
+/// assert!(is_credential_key("//registry.npmjs.org/:_authToken"));
+/// assert!(is_credential_key("_auth"));
+/// assert!(!is_credential_key("@myorg:registry"));
+/// ```
+pub fn is_credential_key(key: &str) -> bool {
+    let field = key.rsplit(':').next().unwrap_or(key);
+    CREDENTIAL_FIELDS.contains(&field)
+}
+
+/// Config fields whose values are secrets. `username` is a credential key
+/// but not a secret on its own.
+const SECRET_FIELDS: &[&str] = &["_authToken", "_auth", "_password"];
+
+/// Check whether a config key holds a secret value that must not be shown.
+pub(crate) fn is_secret_key(key: &str) -> bool {
+    let field = key.rsplit(':').next().unwrap_or(key);
+    SECRET_FIELDS.contains(&field)
+}
+
 /// Decode a base64-encoded password.
 pub fn decode_password(encoded: &str) -> Result<String> {
     let decoded = BASE64.decode(encoded)?;
@@ -140,14 +220,24 @@ pub fn decode_password(encoded: &str) -> Result<String> {
 
 /// Parse legacy `_auth` field (base64-encoded `username:password`).
 pub fn parse_legacy_auth(auth: &str) -> Result<(String, String)> {
-    let decoded = String::from_utf8(BASE64.decode(auth)?)?;
-    let mut parts = decoded.splitn(2, ':');
-    let username = parts.next().unwrap_or("").to_string();
-    let password = parts.next().unwrap_or("").to_string();
-    Ok((username, password))
+    let mut decoded = String::from_utf8(BASE64.decode(auth)?)?;
+    let (username, password) = decoded.split_once(':').unwrap_or((&decoded, ""));
+    let parsed = (username.to_string(), password.to_string());
+    wipe(&mut decoded);
+    Ok(parsed)
 }
 
 impl Credentials {
+    /// Get the kind of credentials without exposing any secret.
+    pub fn kind(&self) -> CredentialKind {
+        match self {
+            Credentials::Token { .. } => CredentialKind::Token,
+            Credentials::BasicAuth { .. } => CredentialKind::BasicAuth,
+            Credentials::LegacyAuth { .. } => CredentialKind::LegacyAuth,
+            Credentials::ClientCertOnly(_) => CredentialKind::ClientCert,
+        }
+    }
+
     /// Get the client certificate if present.
     pub fn client_cert(&self) -> Option<&ClientCert> {
         match self {
@@ -161,37 +251,58 @@ impl Credentials {
     /// Get the token if this is token-based auth.
     pub fn token(&self) -> Option<&str> {
         match self {
-            Credentials::Token { token, .. } => Some(token),
+            Credentials::Token { token, .. } => Some(token.expose_secret()),
             _ => None,
         }
     }
 
+    /// Classify the token and decode its claims, if this is token-based auth.
+    ///
+    /// JWT claims are decoded offline without verifying the signature.
+    pub fn token_info(&self) -> Option<TokenInfo> {
+        self.token().map(TokenInfo::inspect)
+    }
+
     /// Get username and password if available.
     pub fn username_password(&self) -> Option<(&str, &str)> {
         match self {
             Credentials::BasicAuth {
                 username, password, ..
-            } => Some((username, password)),
+            } => Some((username, password.expose_secret())),
             Credentials::LegacyAuth {
                 username, password, ..
-            } => Some((username, password)),
+            } => Some((username, password.expose_secret())),
             _ => None,
         }
     }
 
     /// Get the base64-encoded auth string for HTTP Basic auth.
-    pub fn basic_auth_header(&self) -> Option<String> {
+    pub fn basic_auth_header(&self) -> Option<SecretString> {
         match self {
             Credentials::BasicAuth {
                 username, password, ..
             } => {
-                let auth = format!("{}:{}", username, password);
-                Some(BASE64.encode(auth.as_bytes()))
+                let mut auth = format!("{}:{}", username, password.expose_secret());
+                let encoded = BASE64.encode(auth.as_bytes());
+                wipe(&mut auth);
+                Some(encoded.into())
             }
             Credentials::LegacyAuth { auth, .. } => Some(auth.clone()),
             _ => None,
         }
     }
+
+    /// Get the value of the `Authorization` header.
+    ///
+    /// `Bearer <token>` for tokens and `Basic <base64>` for username and
+    /// password auth. Client certificates are not sent in a header.
+    pub fn authorization_header(&self) -> Option<SecretString> {
+        let header = match self {
+            Credentials::Token { token, .. } => format!("Bearer {}", token.expose_secret()),
+            _ => format!("Basic {}", self.basic_auth_header()?.expose_secret()),
+        };
+        Some(header.into())
+    }
 }
 
 #[cfg(test)]
@@ -227,6 +338,23 @@ mod tests {
         assert_eq!(nerf_dart(&url), "//registry.example.com:8080/npm/");
     }
 
+    #[test]
+    fn test_is_credential_key() {
+        assert!(is_credential_key("//registry.npmjs.org/:_authToken"));
+        assert!(is_credential_key("//example.com:8080/npm/:_password"));
+        assert!(is_credential_key("//example.com/:username"));
+        assert!(is_credential_key("_authToken"));
+        assert!(!is_credential_key("//example.com/:certfile"));
+        assert!(!is_credential_key("registry"));
+    }
+
+    #[test]
+    fn test_is_secret_key() {
+        assert!(is_secret_key("//registry.npmjs.org/:_authToken"));
+        assert!(is_secret_key("_auth"));
+        assert!(!is_secret_key("//example.com/:username"));
+    }
+
     #[test]
     fn test_decode_password() {
         // "password" in base64
@@ -252,33 +380,73 @@ mod tests {
         assert_eq!(password, "pass:word");
     }
 
+    #[test]
+    fn test_auth_type_from_str() {
+        assert_eq!("web".parse::<AuthType>().unwrap(), AuthType::Web);
+        assert_eq!("legacy".parse::<AuthType>().unwrap(), AuthType::Legacy);
+        assert!(matches!(
+            "saml".parse::<AuthType>(),
+            Err(Error::InvalidConfigValue { .. })
+        ));
+        assert_eq!(AuthType::default().to_string(), "web");
+    }
+
+    #[test]
+    fn test_credentials_authorization_header() {
+        let token = Credentials::Token {
+            token: "abc".into(),
+            cert: None,
+        };
+        assert_eq!(
+            token.authorization_header().unwrap().expose_secret(),
+            "Bearer abc"
+        );
+
+        let basic = Credentials::BasicAuth {
+            username: "user".to_string(),
+            password: "pass".into(),
+            cert: None,
+        };
+        assert_eq!(
+            basic.authorization_header().unwrap().expose_secret(),
+            "Basic dXNlcjpwYXNz"
+        );
+
+        let cert = Credentials::ClientCertOnly(ClientCert {
+            certfile: "/c.pem".into(),
+            keyfile: "/k.pem".into(),
+        });
+        assert!(cert.authorization_header().is_none());
+    }
+
     #[test]
     fn test_credentials_basic_auth_header() {
         let creds = Credentials::BasicAuth {
             username: "user".to_string(),
-            password: "password".to_string(),
+            password: "password".into(),
             cert: None,
         };
         assert_eq!(
-            creds.basic_auth_header(),
-            Some("dXNlcjpwYXNzd29yZA==".to_string())
+            creds.basic_auth_header().unwrap().expose_secret(),
+            "dXNlcjpwYXNzd29yZA=="
         );
     }
 
     #[test]
     fn test_credentials_token() {
         let creds = Credentials::Token {
-            token: "my-token".to_string(),
+            token: "my-token".into(),
             cert: None,
         };
         assert_eq!(creds.token(), Some("my-token"));
         assert_eq!(creds.username_password(), None);
+        assert_eq!(creds.kind(), CredentialKind::Token);
     }
 
     #[test]
     fn test_debug_redacts_token() {
         let creds = Credentials::Token {
-            token: "super-secret-token".to_string(),
+            token: "super-secret-token".into(),
             cert: None,
         };
         let debug_output = format!("{:?}", creds);
@@ -296,7 +464,7 @@ mod tests {
     fn test_debug_redacts_basic_auth_password() {
         let creds = Credentials::BasicAuth {
             username: "myuser".to_string(),
-            password: "super-secret-password".to_string(),
+            password: "super-secret-password".into(),
             cert: None,
         };
         let debug_output = format!("{:?}", creds);
@@ -317,9 +485,9 @@ mod tests {
     #[test]
     fn test_debug_redacts_legacy_auth() {
         let creds = Credentials::LegacyAuth {
-            auth: "c2VjcmV0LWF1dGgtc3RyaW5n".to_string(),
+            auth: "c2VjcmV0LWF1dGgtc3RyaW5n".into(),
             username: "legacyuser".to_string(),
-            password: "legacy-secret-password".to_string(),
+            password: "legacy-secret-password".into(),
             cert: None,
         };
         let debug_output = format!("{:?}", creds);
@@ -0,0 +1,45 @@
+//! npm's default values for the config keys this crate knows about.
+
+use crate::registry::DEFAULT_REGISTRY;
+
+/// Default values, as npm would print them, sorted by key.
+///
+/// This is a subset of npm's defaults: keys that this crate interprets or
+/// that commonly appear in `.npmrc` files.
+pub(crate) const NPM_DEFAULTS: &[(&str, &str)] = &[
+    ("always-auth", "false"),
+    ("audit", "true"),
+    ("auth-type", "web"),
+    ("color", "true"),
+    ("fund", "true"),
+    ("global", "false"),
+    ("location", "user"),
+    ("loglevel", "notice"),
+    ("progress", "true"),
+    ("registry", DEFAULT_REGISTRY),
+    ("replace-registry-host", "npmjs"),
+    ("save", "true"),
+    ("strict-ssl", "true"),
+    ("tag", "latest"),
+];
+
+/// Get npm's default value for a key, if known.
+pub(crate) fn npm_default(key: &str) -> Option<&'static str> {
+    NPM_DEFAULTS
+        .binary_search_by_key(&key, |(k, _)| k)
+        .ok()
+        .map(|i| NPM_DEFAULTS[i].1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_npm_defaults_sorted() {
+        assert!(NPM_DEFAULTS.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(npm_default("tag"), Some("latest"));
+        assert_eq!(npm_default("registry"), Some(DEFAULT_REGISTRY));
+        assert_eq!(npm_default("cache"), None);
+    }
+}
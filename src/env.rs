@@ -0,0 +1,195 @@
+//! Export of the effective config as `npm_config_*` environment variables.
+//!
+//! npm, pnpm and node-gyp all read config from the environment. npm maps
+//! `npm_config_foo_bar` to `foo-bar`: it lowercases the name and turns every
+//! `_` except a leading one into `-`, leaving nerf-darted (`//...`) keys
+//! untouched. Keys that don't survive that mapping can't be passed this way.
+
+use crate::config::NpmrcConfig;
+use crate::defaults::npm_default;
+use std::ffi::OsString;
+use std::process::Command;
+
+/// Prefix of config environment variables.
+const ENV_PREFIX: &str = "npm_config_";
+
+/// Get the environment variable npm reads a config key from.
+///
+/// Returns `None` for keys npm's env parser can't represent: keys with
+/// uppercase letters or an `_` after the first character (unless
+/// nerf-darted), and keys containing `=` or NUL.
+///
+/// # Examples
+///
+/// ```
+/// use npmrc_config_rs::npm_config_env_key;
+///
+/// assert_eq!(npm_config_env_key("strict-ssl").as_deref(), Some("npm_config_strict_ssl"));
+/// assert_eq!(npm_config_env_key("_auth").as_deref(), Some("npm_config__auth"));
+/// assert_eq!(
+///     npm_config_env_key("//npm.corp.com/:_authToken").as_deref(),
+///     Some("npm_config_//npm.corp.com/:_authToken")
+/// );
+/// assert_eq!(npm_config_env_key("cache_min"), None);
+/// ```
+pub fn npm_config_env_key(key: &str) -> Option<String> {
+    if key.is_empty() || key.contains(['=', '\0']) {
+        return None;
+    }
+    if key.starts_with("//") {
+        return Some(format!("{}{}", ENV_PREFIX, key));
+    }
+    let rest = key.strip_prefix('_').unwrap_or(key);
+    if rest.contains('_') || key.chars().any(|c| c.is_uppercase()) {
+        return None;
+    }
+    Some(format!("{}{}", ENV_PREFIX, key.replace('-', "_")))
+}
+
+impl NpmrcConfig {
+    /// Get the effective config as `npm_config_*` environment variables.
+    ///
+    /// Covers every key in the merged config, sorted by key. With
+    /// `non_default_only`, keys whose value equals npm's default are left
+    /// out. Keys that can't be represented (see [`npm_config_env_key`]) and
+    /// empty values, which npm ignores in the environment, are skipped.
+    ///
+    /// The result includes credentials; don't log it.
+    pub fn to_env(&self, non_default_only: bool) -> Vec<(String, String)> {
+        self.env_entries(non_default_only)
+            .filter_map(|(key, value)| Some((npm_config_env_key(key)?, value.to_string())))
+            .collect()
+    }
+
+    /// Pass the effective config to a child process through its environment.
+    ///
+    /// Removes `npm_config_*` variables the child would otherwise inherit or
+    /// that were already set on `command` (matched case-insensitively, as npm does) and sets those from
+    /// [`NpmrcConfig::to_env`]. Returns the keys that could not be passed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use npmrc_config_rs::NpmrcConfig;
+    /// use std::process::Command;
+    ///
+    /// let config = NpmrcConfig::load()?;
+    /// let mut npm = Command::new("npm");
+    /// npm.arg("ci");
+    /// for key in config.configure_command(&mut npm, true) {
+    ///     eprintln!("warning: '{}' can't be passed through the environment", key);
+    /// }
+    /// let status = npm.status()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn configure_command(&self, command: &mut Command, non_default_only: bool) -> Vec<String> {
+        let existing: Vec<OsString> = std::env::vars_os()
+            .map(|(name, _)| name)
+            .chain(command.get_envs().map(|(name, _)| name.to_os_string()))
+            .collect();
+        for name in existing {
+            let is_config = name
+                .to_str()
+                .and_then(|name| name.get(..ENV_PREFIX.len()))
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(ENV_PREFIX));
+            if is_config {
+                command.env_remove(name);
+            }
+        }
+
+        let mut skipped = Vec::new();
+        for (key, value) in self.env_entries(non_default_only) {
+            match npm_config_env_key(key) {
+                Some(name) => {
+                    command.env(name, value);
+                }
+                None => skipped.push(key.to_string()),
+            }
+        }
+        skipped
+    }
+
+    /// Get the merged entries to export, without empty values.
+    fn env_entries(&self, non_default_only: bool) -> impl Iterator<Item = (&str, &str)> {
+        self.merged().into_iter().filter(move |(key, value)| {
+            let is_default = npm_default(key) == Some(*value);
+            !(value.is_empty() || (non_default_only && is_default))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LoadOptions;
+    use crate::fs::MemoryFileSystem;
+    use std::sync::Arc;
+
+    fn load() -> NpmrcConfig {
+        let fs = MemoryFileSystem::new()
+            .with_file("/project/package.json", "{}")
+            .with_file(
+                "/project/.npmrc",
+                "tag = latest\n\
+                 strict-ssl = false\n\
+                 @corp:registry = https://npm.corp.com/\n\
+                 //npm.corp.com/:_authToken = secret\n\
+                 cache_min = 10\n\
+                 fetchRetries = 3\n\
+                 message =\n",
+            )
+            .with_file("/home/.npmrc", "strict-ssl = true\ncolor = false\n");
+        NpmrcConfig::load_with_options(LoadOptions {
+            cwd: Some("/project".into()),
+            user_config: Some("/home/.npmrc".into()),
+            fs: Some(Arc::new(fs)),
+            skip_global: true,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_to_env() {
+        let config = load();
+        let env = config.to_env(false);
+        let names: Vec<_> = env.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "npm_config_//npm.corp.com/:_authToken",
+                "npm_config_@corp:registry",
+                "npm_config_color",
+                "npm_config_strict_ssl",
+                "npm_config_tag",
+            ]
+        );
+        // The project layer wins over the user layer
+        assert!(env.contains(&("npm_config_strict_ssl".to_string(), "false".to_string())));
+
+        let non_default: Vec<_> = config
+            .to_env(true)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert!(!non_default.contains(&"npm_config_tag".to_string()));
+        assert!(non_default.contains(&"npm_config_color".to_string()));
+    }
+
+    #[test]
+    fn test_configure_command() {
+        let config = load();
+        let mut command = Command::new("npm");
+        command.env("NPM_CONFIG_STALE", "1");
+        let skipped = config.configure_command(&mut command, true);
+        assert_eq!(skipped, vec!["cache_min", "fetchRetries"]);
+
+        let envs: Vec<_> = command
+            .get_envs()
+            .map(|(name, value)| (name.to_string_lossy().into_owned(), value.is_some()))
+            .collect();
+        assert!(envs.contains(&("npm_config_strict_ssl".to_string(), true)));
+        assert!(envs.contains(&("NPM_CONFIG_STALE".to_string(), false)));
+        assert!(!envs.iter().any(|(name, _)| name == "npm_config_tag"));
+    }
+}
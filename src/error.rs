@@ -18,14 +18,54 @@ pub enum Error {
         source: std::io::Error,
     },
 
+    /// Failed to change the permissions of a config file.
+    #[error("failed to set permissions on {path}: {source}")]
+    SetPermissions {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
     /// Failed to parse INI content.
     #[error("failed to parse INI content from {path}: {message}")]
     ParseIni { path: PathBuf, message: String },
 
+    /// Failed to parse a `package.json` manifest.
+    #[error("failed to parse package manifest {path}: {message}")]
+    ParseManifest { path: PathBuf, message: String },
+
+    /// Failed to parse a `package-lock.json` or `npm-shrinkwrap.json`.
+    #[error("failed to parse lockfile {path}: {message}")]
+    ParseLockfile { path: PathBuf, message: String },
+
     /// Invalid URL in configuration.
     #[error("invalid URL '{url}': {message}")]
     InvalidUrl { url: String, message: String },
 
+    /// Invalid npm package spec.
+    #[error("invalid package spec '{spec}': {message}")]
+    InvalidSpec { spec: String, message: String },
+
+    /// A config key has a value outside its allowed set.
+    #[error("invalid value '{value}' for config key '{key}'")]
+    InvalidConfigValue { key: String, value: String },
+
+    /// A credential value references an environment variable that is not set.
+    #[error("unresolved environment variable ${{{variable}}} in '{key}' ({path})")]
+    UnresolvedEnvVar {
+        key: String,
+        path: PathBuf,
+        variable: String,
+    },
+
+    /// A token helper command failed.
+    #[error("token helper {command} failed: {message}")]
+    TokenHelper { command: PathBuf, message: String },
+
+    /// The merged config could not be deserialized into the requested type.
+    #[error("failed to deserialize config: {0}")]
+    Deserialize(String),
+
     /// Invalid base64 encoding in password field.
     #[error("invalid base64 encoding in _password field")]
     InvalidBase64(#[from] base64::DecodeError),
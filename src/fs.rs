@@ -0,0 +1,312 @@
+//! Filesystem abstraction used for path discovery and config file access.
+//!
+//! Loading goes through the [`FileSystem`] trait so that resolution logic can
+//! be exercised against an in-memory tree ([`MemoryFileSystem`]) instead of
+//! real temp directories.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+/// Filesystem operations needed to discover and load `.npmrc` files.
+///
+/// Everything is read-only except [`FileSystem::set_permissions`], which
+/// [`PermissionIssue::fix`](crate::permissions::PermissionIssue::fix) uses.
+pub trait FileSystem: fmt::Debug + Send + Sync {
+    /// Check whether `path` is an existing regular file.
+    fn is_file(&self, path: &Path) -> bool;
+
+    /// Check whether `path` is an existing directory.
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// Read the entire contents of a file as UTF-8.
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+
+    /// Read the entire contents of a file as bytes.
+    ///
+    /// Defaults to [`FileSystem::read_to_string`], so binary files such as
+    /// the git index can't be read unless this is overridden.
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.read_to_string(path).map(String::into_bytes)
+    }
+
+    /// Check whether `path` exists as either a file or a directory.
+    fn exists(&self, path: &Path) -> bool {
+        self.is_file(path) || self.is_dir(path)
+    }
+
+    /// Get the Unix permission bits and owner of a file.
+    ///
+    /// Returns `Ok(None)` where permissions are not available, which is the
+    /// default.
+    fn permissions(&self, _path: &Path) -> io::Result<Option<FilePermissions>> {
+        Ok(None)
+    }
+
+    /// Set the Unix permission bits of a file.
+    ///
+    /// Fails with [`io::ErrorKind::Unsupported`] by default.
+    fn set_permissions(&self, _path: &Path, _mode: u32) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "setting permissions is not supported",
+        ))
+    }
+}
+
+/// Unix permission bits and owner of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilePermissions {
+    /// Permission bits (`mode & 0o777`).
+    pub mode: u32,
+    /// Owner uid.
+    pub uid: u32,
+}
+
+/// [`FileSystem`] backed by `std::fs`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealFileSystem;
+
+impl FileSystem for RealFileSystem {
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    #[cfg(unix)]
+    fn permissions(&self, path: &Path) -> io::Result<Option<FilePermissions>> {
+        use std::os::unix::fs::MetadataExt;
+
+        let metadata = std::fs::metadata(path)?;
+        Ok(Some(FilePermissions {
+            mode: metadata.mode() & 0o777,
+            uid: metadata.uid(),
+        }))
+    }
+
+    #[cfg(unix)]
+    fn set_permissions(&self, path: &Path, mode: u32) -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+    }
+
+    /// Does nothing: there are no Unix permission bits to change.
+    #[cfg(not(unix))]
+    fn set_permissions(&self, _path: &Path, _mode: u32) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// In-memory [`FileSystem`] for tests.
+///
+/// Adding a file implicitly creates all of its parent directories. Files
+/// have no permissions unless given with [`MemoryFileSystem::with_permissions`];
+/// [`FileSystem::set_permissions`] only changes the mode of files that have
+/// them.
+/// The `Debug` implementation lists paths only, never file contents.
+///
+/// # Examples
+///
+/// ```
+/// use npmrc_config_rs::{FileSystem, MemoryFileSystem};
+/// use std::path::Path;
+///
+/// let fs = MemoryFileSystem::new()
+///     .with_file("/project/package.json", "{}")
+///     .with_file("/project/.npmrc", "registry = https://example.com/");
+///
+/// assert!(fs.is_file(Path::new("/project/.npmrc")));
+/// assert!(fs.is_dir(Path::new("/project")));
+/// ```
+#[derive(Default)]
+pub struct MemoryFileSystem {
+    files: HashMap<PathBuf, Vec<u8>>,
+    dirs: HashSet<PathBuf>,
+    permissions: Mutex<HashMap<PathBuf, FilePermissions>>,
+}
+
+impl Clone for MemoryFileSystem {
+    fn clone(&self) -> Self {
+        MemoryFileSystem {
+            files: self.files.clone(),
+            dirs: self.dirs.clone(),
+            permissions: Mutex::new(self.lock_permissions().clone()),
+        }
+    }
+}
+
+impl MemoryFileSystem {
+    /// Create an empty in-memory filesystem.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file, returning `self` for chaining.
+    pub fn with_file(mut self, path: impl Into<PathBuf>, content: impl Into<Vec<u8>>) -> Self {
+        self.add_file(path, content);
+        self
+    }
+
+    /// Add a directory, returning `self` for chaining.
+    pub fn with_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.add_dir(path);
+        self
+    }
+
+    /// Add or replace a file and create its parent directories.
+    pub fn add_file(&mut self, path: impl Into<PathBuf>, content: impl Into<Vec<u8>>) {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            self.add_dir(parent);
+        }
+        self.files.insert(path, content.into());
+    }
+
+    /// Set the permissions reported for a file, returning `self` for chaining.
+    pub fn with_permissions(self, path: impl Into<PathBuf>, permissions: FilePermissions) -> Self {
+        self.lock_permissions().insert(path.into(), permissions);
+        self
+    }
+
+    /// Lock the permissions map.
+    fn lock_permissions(&self) -> MutexGuard<'_, HashMap<PathBuf, FilePermissions>> {
+        self.permissions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Fail with `NotFound` unless `path` is a file.
+    fn require_file(&self, path: &Path) -> io::Result<()> {
+        if self.is_file(path) {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                path.display().to_string(),
+            ))
+        }
+    }
+
+    /// Add a directory and all of its ancestors.
+    pub fn add_dir(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        for ancestor in path.ancestors() {
+            if ancestor.as_os_str().is_empty() || !self.dirs.insert(ancestor.to_path_buf()) {
+                break;
+            }
+        }
+    }
+}
+
+impl fmt::Debug for MemoryFileSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut files: Vec<_> = self.files.keys().collect();
+        files.sort();
+        let mut dirs: Vec<_> = self.dirs.iter().collect();
+        dirs.sort();
+        f.debug_struct("MemoryFileSystem")
+            .field("files", &files)
+            .field("dirs", &dirs)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.dirs.contains(path)
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.display().to_string()))
+    }
+
+    fn permissions(&self, path: &Path) -> io::Result<Option<FilePermissions>> {
+        self.require_file(path)?;
+        Ok(self.lock_permissions().get(path).copied())
+    }
+
+    fn set_permissions(&self, path: &Path, mode: u32) -> io::Result<()> {
+        self.require_file(path)?;
+        if let Some(permissions) = self.lock_permissions().get_mut(path) {
+            permissions.mode = mode & 0o777;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_fs_creates_parent_dirs() {
+        let fs = MemoryFileSystem::new().with_file("/a/b/c.txt", "content");
+
+        assert!(fs.is_file(Path::new("/a/b/c.txt")));
+        assert!(fs.is_dir(Path::new("/a/b")));
+        assert!(fs.is_dir(Path::new("/a")));
+        assert!(fs.is_dir(Path::new("/")));
+        assert!(!fs.is_file(Path::new("/a/b")));
+    }
+
+    #[test]
+    fn test_memory_fs_read() {
+        let fs = MemoryFileSystem::new().with_file("/a.txt", "hello");
+
+        assert_eq!(fs.read_to_string(Path::new("/a.txt")).unwrap(), "hello");
+        let err = fs.read_to_string(Path::new("/missing")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        let fs = MemoryFileSystem::new().with_file("/bin", vec![0xff, 0x00]);
+        assert_eq!(fs.read(Path::new("/bin")).unwrap(), [0xff, 0x00]);
+        let err = fs.read_to_string(Path::new("/bin")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_memory_fs_debug_lists_paths_only() {
+        let fs = MemoryFileSystem::new().with_file("/a/.npmrc", "_authToken = secret");
+        let debug = format!("{:?}", fs);
+        assert!(debug.contains("/a/.npmrc"));
+        assert!(!debug.contains("secret"));
+    }
+
+    #[test]
+    fn test_memory_fs_exists() {
+        let fs = MemoryFileSystem::new()
+            .with_dir("/project/node_modules")
+            .with_file("/project/package.json", "{}");
+
+        assert!(fs.exists(Path::new("/project/node_modules")));
+        assert!(fs.exists(Path::new("/project/package.json")));
+        assert!(!fs.exists(Path::new("/project/.npmrc")));
+    }
+}
@@ -0,0 +1,416 @@
+//! Generation of minimal `.npmrc` files.
+//!
+//! Build stages (e.g. Docker) often need a throwaway `.npmrc` with just the
+//! registries and auth for the project's dependencies, rather than a copy of
+//! a developer's whole config.
+
+use crate::auth::{is_secret_key, nerf_dart};
+use crate::config::NpmrcConfig;
+use crate::error::Result;
+use crate::lockfile::read_lockfile;
+use crate::redact::redact;
+use crate::registry::{extract_scope, scope_registry_key};
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+use std::sync::LazyLock;
+use url::Url;
+
+/// Nerf-darted fields copied for each registry, in output order.
+const AUTH_FIELDS: &[&str] = &[
+    "_authToken",
+    "_auth",
+    "username",
+    "_password",
+    "certfile",
+    "keyfile",
+    "always-auth",
+];
+
+/// `${VAR}` references in a value.
+static ENV_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$\{([^${}?]+)\??\}").unwrap());
+
+/// A generated `.npmrc` and the environment variables it needs.
+#[derive(Clone, PartialEq, Eq)]
+pub struct MinimalNpmrc {
+    /// File content, one `key = value` line per entry.
+    pub content: String,
+    /// Environment variables referenced by `${VAR}` placeholders, sorted.
+    pub env_vars: Vec<String>,
+}
+
+impl fmt::Debug for MinimalNpmrc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MinimalNpmrc")
+            .field("content", &redact(&self.content))
+            .field("env_vars", &self.env_vars)
+            .finish()
+    }
+}
+
+/// Build the placeholder variable for a secret field, e.g.
+/// `//npm.corp.com/npm/` + `_authToken` -> `NPM_CORP_COM_NPM_AUTH_TOKEN`.
+fn placeholder_var(nerf: &str, field: &str) -> String {
+    let field = match field {
+        "_authToken" => "AUTH_TOKEN",
+        "_auth" => "AUTH",
+        _ => "PASSWORD",
+    };
+    let mut var = String::new();
+    for c in nerf.trim_matches('/').chars() {
+        if c.is_ascii_alphanumeric() {
+            var.push(c.to_ascii_uppercase());
+        } else if !var.ends_with('_') {
+            var.push('_');
+        }
+    }
+    format!("{}_{}", var, field)
+}
+
+impl NpmrcConfig {
+    /// Generate a minimal `.npmrc` for installing the given packages.
+    ///
+    /// Includes `registry` if it is set and any package uses it, the
+    /// `@scope:registry` of every scope that has one, and the nerf-darted
+    /// auth of each of those registries (`_authToken`, `_auth`, `username`,
+    /// `_password`, `certfile`, `keyfile`, `always-auth`).
+    ///
+    /// With `env_placeholders`, literal secrets are replaced by `${VAR}`
+    /// placeholders named after the registry, e.g.
+    /// `${NPM_CORP_COM_AUTH_TOKEN}`, numbered (`_2`, `_3`, ...) when two
+    /// registries or an existing reference would share a name. Values written
+    /// with `${VAR}` references are copied as written either way, as kept in
+    /// [`ConfigData::unexpanded`](crate::ConfigData::unexpanded), so a token
+    /// read from the environment is never written out. Every referenced
+    /// variable is listed in [`MinimalNpmrc::env_vars`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use npmrc_config_rs::NpmrcConfig;
+    ///
+    /// let config = NpmrcConfig::load()?;
+    /// let npmrc = config.minimal_npmrc(["@corp/ui", "lodash"], true);
+    /// std::fs::write("build/.npmrc", &npmrc.content)?;
+    /// for var in &npmrc.env_vars {
+    ///     println!("--build-arg {}", var);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn minimal_npmrc<I, S>(&self, packages: I, env_placeholders: bool) -> MinimalNpmrc
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.build_minimal_npmrc(packages, BTreeSet::new(), env_placeholders)
+    }
+
+    /// Generate a minimal `.npmrc` for the packages in a lockfile.
+    ///
+    /// Like [`NpmrcConfig::minimal_npmrc`] for every package in a
+    /// `package-lock.json` or `npm-shrinkwrap.json` (v1, v2 or v3). Auth
+    /// configured for the hosts of `resolved` tarball URLs is included too.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ReadFile`](crate::Error::ReadFile) if the lockfile
+    /// can't be read and [`Error::ParseLockfile`](crate::Error::ParseLockfile)
+    /// if it is not a lockfile.
+    pub fn minimal_npmrc_for_lockfile(
+        &self,
+        lockfile: impl AsRef<Path>,
+        env_placeholders: bool,
+    ) -> Result<MinimalNpmrc> {
+        let packages = read_lockfile(self.fs(), lockfile.as_ref())?;
+        let tarball_auth = packages
+            .iter()
+            .filter_map(|p| p.resolved.as_deref())
+            .filter_map(|resolved| Url::parse(resolved).ok())
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .filter_map(|url| self.own_auth_nerf_dart(&self.rewrite_resolved_url(&url)))
+            .collect();
+        let names = packages.into_iter().map(|p| p.name);
+        Ok(self.build_minimal_npmrc(names, tarball_auth, env_placeholders))
+    }
+
+    /// Generate a minimal `.npmrc` for packages plus extra auth nerf darts.
+    fn build_minimal_npmrc<I, S>(
+        &self,
+        packages: I,
+        mut auth_nerf_darts: BTreeSet<String>,
+        env_placeholders: bool,
+    ) -> MinimalNpmrc
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut uses_default = false;
+        let mut scopes: BTreeMap<String, Url> = BTreeMap::new();
+        for package in packages {
+            let package = package.as_ref();
+            let scoped = extract_scope(package).and_then(|scope| {
+                let key = scope_registry_key(scope);
+                self.get(&key)?;
+                Some((key, self.registry_for(package)))
+            });
+            match scoped {
+                Some((key, url)) => {
+                    scopes.insert(key, url);
+                }
+                None => uses_default = true,
+            }
+        }
+
+        let mut lines = Vec::new();
+        if uses_default {
+            auth_nerf_darts.insert(nerf_dart(&self.default_registry()));
+            if self.get("registry").is_some() {
+                lines.push(format!("registry = {}", self.default_registry()));
+            }
+        }
+        for (key, url) in &scopes {
+            auth_nerf_darts.insert(nerf_dart(url));
+            lines.push(format!("{} = {}", key, url));
+        }
+
+        // Values to write, with `None` for secrets replaced by a placeholder
+        let mut auth = Vec::new();
+        for nerf in &auth_nerf_darts {
+            for field in AUTH_FIELDS {
+                let key = format!("{}:{}", nerf, field);
+                let Some(value) = self.get(&key) else {
+                    continue;
+                };
+                let value = match self.unexpanded(&key).unwrap_or(value) {
+                    value if ENV_REFERENCE.is_match(value) => Some(value.to_string()),
+                    _ if env_placeholders && is_secret_key(field) => None,
+                    value => Some(value.to_string()),
+                };
+                auth.push((key, nerf, field, value));
+            }
+        }
+
+        let mut env_vars: BTreeSet<String> = auth
+            .iter()
+            .filter_map(|(.., value)| value.as_deref())
+            .flat_map(|value| ENV_REFERENCE.captures_iter(value))
+            .map(|caps| caps[1].to_string())
+            .collect();
+        for (key, nerf, field, value) in auth {
+            let value = value.unwrap_or_else(|| {
+                // Hosts like `a.b.com` and `a-b.com` share a name; number the rest
+                let base = placeholder_var(nerf, field);
+                let var = (1..)
+                    .map(|n| match n {
+                        1 => base.clone(),
+                        n => format!("{}_{}", base, n),
+                    })
+                    .find(|var| !env_vars.contains(var))
+                    .expect("unbounded range");
+                env_vars.insert(var.clone());
+                format!("${{{}}}", var)
+            });
+            lines.push(format!("{} = {}", key, value));
+        }
+
+        let mut content = lines.join("\n");
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        MinimalNpmrc {
+            content,
+            env_vars: env_vars.into_iter().collect(),
+        }
+    }
+
+    /// Get the value of `key` as written, before expansion, if it contains a
+    /// `${VAR}` reference.
+    fn unexpanded(&self, key: &str) -> Option<&str> {
+        self.layers()
+            .iter()
+            .find(|layer| layer.data.get(key).is_some())?
+            .data
+            .unexpanded
+            .get(key)
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConfigLayer, LoadOptions};
+    use crate::fs::MemoryFileSystem;
+    use std::sync::Arc;
+
+    const NPMRC: &str = "registry = https://mirror.corp.com/\n\
+                         @corp:registry = https://npm.corp.com/npm/\n\
+                         @other:registry = https://other.com/\n\
+                         //mirror.corp.com/:_authToken = mirror-token\n\
+                         //npm.corp.com/npm/:_authToken = corp-token\n\
+                         //npm.corp.com/npm/:always-auth = true\n\
+                         //other.com/:_authToken = other-token\n\
+                         //cdn.corp.com/:_authToken = ${CDN_TOKEN}\n\
+                         color = false\n";
+
+    fn load(lockfile: &str) -> NpmrcConfig {
+        let fs = MemoryFileSystem::new()
+            .with_file("/project/package.json", "{}")
+            .with_file("/project/package-lock.json", lockfile)
+            .with_file("/project/.npmrc", NPMRC);
+        NpmrcConfig::load_with_options(LoadOptions {
+            cwd: Some("/project".into()),
+            fs: Some(Arc::new(fs)),
+            skip_user: true,
+            skip_global: true,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_placeholder_var() {
+        assert_eq!(
+            placeholder_var("//npm.corp.com/npm/", "_authToken"),
+            "NPM_CORP_COM_NPM_AUTH_TOKEN"
+        );
+        assert_eq!(
+            placeholder_var("//localhost:4873/", "_password"),
+            "LOCALHOST_4873_PASSWORD"
+        );
+    }
+
+    #[test]
+    fn test_minimal_npmrc_literal() {
+        let npmrc = load("{}").minimal_npmrc(["@corp/ui"], false);
+        assert_eq!(
+            npmrc.content,
+            "@corp:registry = https://npm.corp.com/npm/\n\
+             //npm.corp.com/npm/:_authToken = corp-token\n\
+             //npm.corp.com/npm/:always-auth = true\n"
+        );
+        assert!(npmrc.env_vars.is_empty());
+        assert!(!format!("{:?}", npmrc).contains("corp-token"));
+    }
+
+    #[test]
+    fn test_minimal_npmrc_placeholders() {
+        let npmrc = load("{}").minimal_npmrc(["lodash", "@corp/ui", "@nope/pkg"], true);
+        assert_eq!(
+            npmrc.content,
+            "registry = https://mirror.corp.com/\n\
+             @corp:registry = https://npm.corp.com/npm/\n\
+             //mirror.corp.com/:_authToken = ${MIRROR_CORP_COM_AUTH_TOKEN}\n\
+             //npm.corp.com/npm/:_authToken = ${NPM_CORP_COM_NPM_AUTH_TOKEN}\n\
+             //npm.corp.com/npm/:always-auth = true\n"
+        );
+        assert_eq!(
+            npmrc.env_vars,
+            vec!["MIRROR_CORP_COM_AUTH_TOKEN", "NPM_CORP_COM_NPM_AUTH_TOKEN"]
+        );
+    }
+
+    #[test]
+    fn test_minimal_npmrc_keeps_env_references() {
+        std::env::set_var("GENERATE_TEST_TOKEN", "expanded-token");
+        let fs = MemoryFileSystem::new()
+            .with_file("/project/package.json", "{}")
+            .with_file(
+                "/project/.npmrc",
+                "registry = https://npm.corp.com/\n\
+                 //npm.corp.com/:_authToken = ${GENERATE_TEST_TOKEN}\n\
+                 //npm.corp.com/:tokenHelper = /usr/bin/helper\n",
+            );
+        let config = NpmrcConfig::load_with_options(LoadOptions {
+            cwd: Some("/project".into()),
+            fs: Some(Arc::new(fs)),
+            skip_user: true,
+            skip_global: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        for env_placeholders in [false, true] {
+            let npmrc = config.minimal_npmrc(["lodash"], env_placeholders);
+            assert_eq!(
+                npmrc.content,
+                "registry = https://npm.corp.com/\n\
+                 //npm.corp.com/:_authToken = ${GENERATE_TEST_TOKEN}\n"
+            );
+            assert_eq!(npmrc.env_vars, vec!["GENERATE_TEST_TOKEN"]);
+        }
+
+        // In-memory layers keep their references too
+        let mut config = config;
+        config.push_layer(
+            ConfigLayer::parse(
+                "cli",
+                i32::MAX,
+                "//npm.corp.com/:_authToken = ${GENERATE_TEST_TOKEN}-cli\n",
+            )
+            .unwrap(),
+        );
+        let npmrc = config.minimal_npmrc(["lodash"], false);
+        assert!(
+            npmrc
+                .content
+                .contains("//npm.corp.com/:_authToken = ${GENERATE_TEST_TOKEN}-cli\n"),
+            "{}",
+            npmrc.content
+        );
+    }
+
+    #[test]
+    fn test_minimal_npmrc_unique_placeholders() {
+        let mut config = load("{}");
+        config.push_layer(
+            ConfigLayer::parse(
+                "cli",
+                i32::MAX,
+                "@a:registry = https://npm.corp.com/\n\
+                 @b:registry = https://npm-corp.com/\n\
+                 //npm.corp.com/:_authToken = a-token\n\
+                 //npm-corp.com/:_authToken = b-token\n",
+            )
+            .unwrap(),
+        );
+        let npmrc = config.minimal_npmrc(["@a/x", "@b/y"], true);
+        assert_eq!(
+            npmrc.content,
+            "@a:registry = https://npm.corp.com/\n\
+             @b:registry = https://npm-corp.com/\n\
+             //npm-corp.com/:_authToken = ${NPM_CORP_COM_AUTH_TOKEN}\n\
+             //npm.corp.com/:_authToken = ${NPM_CORP_COM_AUTH_TOKEN_2}\n"
+        );
+        assert_eq!(
+            npmrc.env_vars,
+            vec!["NPM_CORP_COM_AUTH_TOKEN", "NPM_CORP_COM_AUTH_TOKEN_2"]
+        );
+    }
+
+    #[test]
+    fn test_minimal_npmrc_for_lockfile() {
+        let config = load(
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": {"name": "app"},
+                    "node_modules/@other/a": {"resolved": "https://other.com/@other/a/-/a-1.0.0.tgz"},
+                    "node_modules/@other/b": {"resolved": "https://cdn.corp.com/b-1.0.0.tgz"}
+                }
+            }"#,
+        );
+        let npmrc = config
+            .minimal_npmrc_for_lockfile("/project/package-lock.json", false)
+            .unwrap();
+        assert_eq!(
+            npmrc.content,
+            "@other:registry = https://other.com/\n\
+             //cdn.corp.com/:_authToken = ${CDN_TOKEN}\n\
+             //other.com/:_authToken = other-token\n"
+        );
+        assert_eq!(npmrc.env_vars, vec!["CDN_TOKEN"]);
+    }
+}
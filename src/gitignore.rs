@@ -0,0 +1,422 @@
+//! Minimal `.gitignore` and git index evaluation without invoking git.
+//!
+//! Supports the rules that matter for deciding whether a config file would be
+//! committed: per-directory `.gitignore` files, `.git/info/exclude`,
+//! negation, directory-only patterns, anchoring and `**`. The global
+//! `core.excludesFile` is not consulted. Files that are already tracked are
+//! found by reading the paths in `.git/index` (versions 2 to 4).
+
+use crate::fs::FileSystem;
+use glob::{MatchOptions, Pattern};
+use std::io;
+use std::path::{Path, PathBuf};
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// A single pattern line from an ignore file.
+#[derive(Debug)]
+struct IgnoreRule {
+    /// Directory the pattern is relative to.
+    base: PathBuf,
+    pattern: Pattern,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl IgnoreRule {
+    /// Parse one ignore-file line, returning `None` for blanks and comments.
+    fn parse(base: &Path, line: &str) -> Option<Self> {
+        let line = if line.ends_with("\\ ") {
+            line
+        } else {
+            line.trim_end()
+        };
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        if line.is_empty() {
+            return None;
+        }
+
+        // A pattern without an inner slash matches at any depth
+        let glob = match line.strip_prefix('/') {
+            Some(anchored) => anchored.to_string(),
+            None if line.contains('/') => line.to_string(),
+            None => format!("**/{}", line),
+        };
+
+        Some(IgnoreRule {
+            base: base.to_path_buf(),
+            pattern: Pattern::new(&glob.replace("\\ ", " ")).ok()?,
+            negated,
+            dir_only,
+        })
+    }
+
+    /// Check whether this rule applies to `path`.
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        match path.strip_prefix(&self.base) {
+            Ok(relative) if !relative.as_os_str().is_empty() => {
+                self.pattern.matches_path_with(relative, MATCH_OPTIONS)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Find the root of the git work tree containing `start`.
+pub(crate) fn find_repo_root(fs: &dyn FileSystem, start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| fs.exists(&dir.join(".git")))
+        .map(Path::to_path_buf)
+}
+
+/// Check whether `path` inside `repo_root` is excluded by ignore rules.
+pub(crate) fn is_ignored(fs: &dyn FileSystem, repo_root: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(repo_root) else {
+        return false;
+    };
+
+    // Lowest precedence first: info/exclude, then .gitignore files from the
+    // root down to the file's directory.
+    let mut rules = Vec::new();
+    let mut load = |base: &Path, file: PathBuf| {
+        if let Ok(content) = fs.read_to_string(&file) {
+            rules.extend(content.lines().filter_map(|l| IgnoreRule::parse(base, l)));
+        }
+    };
+    load(
+        repo_root,
+        repo_root.join(".git").join("info").join("exclude"),
+    );
+    let mut dir = repo_root.to_path_buf();
+    load(&dir, dir.join(".gitignore"));
+    let parents: Vec<_> = relative
+        .parent()
+        .into_iter()
+        .flat_map(|p| p.components())
+        .collect();
+    for component in parents {
+        dir.push(component);
+        load(&dir, dir.join(".gitignore"));
+    }
+
+    let excluded = |candidate: &Path, is_dir: bool| {
+        rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(candidate, is_dir))
+            .is_some_and(|rule| !rule.negated)
+    };
+
+    // A file inside an excluded directory cannot be re-included
+    let mut candidate = repo_root.to_path_buf();
+    let components: Vec<_> = relative.components().collect();
+    for (idx, component) in components.iter().enumerate() {
+        candidate.push(component);
+        let is_dir = idx + 1 < components.len();
+        if excluded(&candidate, is_dir) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Find the git directory of a work tree, following a `.git` file
+/// (`gitdir: <path>`) as used by worktrees and submodules.
+fn git_dir(fs: &dyn FileSystem, repo_root: &Path) -> Option<PathBuf> {
+    let dot_git = repo_root.join(".git");
+    if !fs.is_file(&dot_git) {
+        return Some(dot_git);
+    }
+    let content = fs.read_to_string(&dot_git).ok()?;
+    let dir = content.trim().strip_prefix("gitdir:")?.trim();
+    Some(repo_root.join(dir))
+}
+
+/// Check whether `path` inside `repo_root` is tracked, i.e. listed in the
+/// git index.
+///
+/// A missing index means nothing is tracked. An index that can't be read or
+/// parsed is assumed to track the file, so it is never reported as safe by
+/// mistake.
+pub(crate) fn is_tracked(fs: &dyn FileSystem, repo_root: &Path, path: &Path) -> bool {
+    let Some(wanted) = path
+        .strip_prefix(repo_root)
+        .ok()
+        .and_then(|relative| relative.to_str())
+        .map(|relative| relative.replace(std::path::MAIN_SEPARATOR, "/"))
+    else {
+        return false;
+    };
+    let Some(git_dir) = git_dir(fs, repo_root) else {
+        return true;
+    };
+
+    let index = match fs.read(&git_dir.join("index")) {
+        Ok(index) => index,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return false,
+        Err(_) => return true,
+    };
+    let hash_len = match fs.read_to_string(&git_dir.join("config")) {
+        Ok(config) if is_sha256_repo(&config) => 32,
+        _ => 20,
+    };
+    match index_paths(&index, hash_len) {
+        Some(paths) => paths.iter().any(|p| p == wanted.as_bytes()),
+        None => true,
+    }
+}
+
+/// Check whether a git config sets `extensions.objectFormat = sha256`.
+fn is_sha256_repo(config: &str) -> bool {
+    config.lines().any(|line| {
+        let line = line.trim().to_ascii_lowercase();
+        line.replace(' ', "") == "objectformat=sha256"
+    })
+}
+
+/// Read the paths of all entries in a git index file.
+///
+/// Returns `None` if the data is not a version 2, 3 or 4 index.
+fn index_paths(index: &[u8], hash_len: usize) -> Option<Vec<Vec<u8>>> {
+    let mut reader = IndexReader {
+        data: index,
+        pos: 0,
+    };
+    if reader.take(4)? != b"DIRC" {
+        return None;
+    }
+    let version = reader.u32()?;
+    if !(2..=4).contains(&version) {
+        return None;
+    }
+    let count = reader.u32()?;
+
+    let mut paths: Vec<Vec<u8>> = Vec::new();
+    for _ in 0..count {
+        let start = reader.pos;
+        // ctime, mtime, dev, ino, mode, uid, gid and size, then the hash
+        reader.take(40 + hash_len)?;
+        let flags = reader.u16()?;
+        if version >= 3 && flags & 0x4000 != 0 {
+            reader.take(2)?;
+        }
+
+        if version == 4 {
+            // The path is stored as a suffix of the previous entry's path
+            let strip = reader.varint()?;
+            let previous = paths.last().map(Vec::as_slice).unwrap_or_default();
+            let keep = previous.len().checked_sub(strip)?;
+            let mut path = previous[..keep].to_vec();
+            path.extend_from_slice(reader.until_nul()?);
+            paths.push(path);
+        } else {
+            let path = reader.until_nul()?.to_vec();
+            // Entries are NUL-padded to a multiple of 8 bytes
+            let len = reader.pos - 1 - start;
+            reader.pos = start + (len + 8) / 8 * 8;
+            paths.push(path);
+        }
+    }
+    Some(paths)
+}
+
+/// Cursor over the bytes of a git index.
+struct IndexReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> IndexReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_be_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    /// Read git's offset varint, as used for v4 path prefixes.
+    fn varint(&mut self) -> Option<usize> {
+        let mut byte = self.take(1)?[0];
+        let mut value = usize::from(byte & 0x7f);
+        while byte & 0x80 != 0 {
+            byte = self.take(1)?[0];
+            value = value
+                .checked_add(1)?
+                .checked_mul(128)?
+                .checked_add(usize::from(byte & 0x7f))?;
+        }
+        Some(value)
+    }
+
+    /// Read bytes up to a NUL, consuming the NUL.
+    fn until_nul(&mut self) -> Option<&'a [u8]> {
+        let rest = self.data.get(self.pos..)?;
+        let len = rest.iter().position(|&b| b == 0)?;
+        self.pos += len + 1;
+        Some(&rest[..len])
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::fs::MemoryFileSystem;
+
+    /// Build a version 2 git index listing `paths`, which must be sorted.
+    pub(crate) fn git_index(paths: &[&str]) -> Vec<u8> {
+        let mut index = b"DIRC".to_vec();
+        index.extend_from_slice(&2u32.to_be_bytes());
+        index.extend_from_slice(&(paths.len() as u32).to_be_bytes());
+        for path in paths {
+            let start = index.len();
+            index.extend_from_slice(&[0; 60]);
+            index.extend_from_slice(&(path.len() as u16).to_be_bytes());
+            index.extend_from_slice(path.as_bytes());
+            let len = index.len() - start;
+            index.resize(start + (len + 8) / 8 * 8, 0);
+        }
+        index
+    }
+
+    fn ignored(fs: &MemoryFileSystem, path: &str) -> bool {
+        is_ignored(fs, Path::new("/repo"), Path::new(path))
+    }
+
+    #[test]
+    fn test_find_repo_root() {
+        let fs = MemoryFileSystem::new()
+            .with_dir("/repo/.git")
+            .with_dir("/repo/packages/app");
+        assert_eq!(
+            find_repo_root(&fs, Path::new("/repo/packages/app")),
+            Some(PathBuf::from("/repo"))
+        );
+        assert_eq!(find_repo_root(&fs, Path::new("/elsewhere")), None);
+    }
+
+    #[test]
+    fn test_basename_and_anchored_patterns() {
+        let fs = MemoryFileSystem::new()
+            .with_dir("/repo/.git")
+            .with_file("/repo/.gitignore", "# secrets\n.npmrc\n/build\n");
+        assert!(ignored(&fs, "/repo/.npmrc"));
+        assert!(ignored(&fs, "/repo/packages/app/.npmrc"));
+        assert!(ignored(&fs, "/repo/build/out.js"));
+        assert!(!ignored(&fs, "/repo/packages/build/out.js"));
+        assert!(!ignored(&fs, "/repo/package.json"));
+    }
+
+    #[test]
+    fn test_negation_and_nested_gitignore() {
+        let fs = MemoryFileSystem::new()
+            .with_dir("/repo/.git")
+            .with_file("/repo/.gitignore", ".npmrc\n")
+            .with_file("/repo/app/.gitignore", "!.npmrc\n");
+        assert!(ignored(&fs, "/repo/.npmrc"));
+        assert!(!ignored(&fs, "/repo/app/.npmrc"));
+    }
+
+    #[test]
+    fn test_excluded_directory_cannot_be_reincluded() {
+        let fs = MemoryFileSystem::new()
+            .with_dir("/repo/.git")
+            .with_file("/repo/.gitignore", "config/\n!config/.npmrc\n");
+        assert!(ignored(&fs, "/repo/config/.npmrc"));
+        // Directory-only patterns don't match files
+        let fs = MemoryFileSystem::new()
+            .with_dir("/repo/.git")
+            .with_file("/repo/.gitignore", ".npmrc/\n");
+        assert!(!ignored(&fs, "/repo/.npmrc"));
+    }
+
+    #[test]
+    fn test_info_exclude_and_double_star() {
+        let fs = MemoryFileSystem::new()
+            .with_file("/repo/.git/info/exclude", "apps/**/.npmrc\n")
+            .with_file("/repo/.gitignore", "");
+        assert!(ignored(&fs, "/repo/apps/web/deep/.npmrc"));
+        assert!(!ignored(&fs, "/repo/.npmrc"));
+    }
+
+    #[test]
+    fn test_is_tracked() {
+        let fs = MemoryFileSystem::new().with_file(
+            "/repo/.git/index",
+            git_index(&[".gitignore", "app/.npmrc", "app/package.json"]),
+        );
+        let tracked = |path: &str| is_tracked(&fs, Path::new("/repo"), Path::new(path));
+        assert!(tracked("/repo/app/.npmrc"));
+        assert!(tracked("/repo/.gitignore"));
+        assert!(!tracked("/repo/.npmrc"));
+        assert!(!tracked("/repo/app/.npmrc.bak"));
+
+        // No index yet: nothing is tracked
+        let fs = MemoryFileSystem::new().with_dir("/repo/.git");
+        assert!(!is_tracked(
+            &fs,
+            Path::new("/repo"),
+            Path::new("/repo/.npmrc")
+        ));
+
+        // Unreadable index: assume tracked
+        let fs = MemoryFileSystem::new().with_file("/repo/.git/index", "garbage");
+        assert!(is_tracked(
+            &fs,
+            Path::new("/repo"),
+            Path::new("/repo/.npmrc")
+        ));
+    }
+
+    #[test]
+    fn test_is_tracked_worktree_and_v4_index() {
+        // v4: "a/.npmrc", then "a/package.json" stored as strip 6 + "package.json"
+        let mut index = b"DIRC".to_vec();
+        index.extend_from_slice(&4u32.to_be_bytes());
+        index.extend_from_slice(&2u32.to_be_bytes());
+        for (strip, suffix) in [(0u8, "a/.npmrc"), (6, "package.json")] {
+            index.extend_from_slice(&[0; 60]);
+            index.extend_from_slice(&(suffix.len() as u16).to_be_bytes());
+            index.push(strip);
+            index.extend_from_slice(suffix.as_bytes());
+            index.push(0);
+        }
+        assert_eq!(
+            index_paths(&index, 20).unwrap(),
+            [b"a/.npmrc".to_vec(), b"a/package.json".to_vec()]
+        );
+
+        let fs = MemoryFileSystem::new()
+            .with_file("/repo/.git", "gitdir: /main/.git/worktrees/repo\n")
+            .with_file("/main/.git/worktrees/repo/index", index);
+        let tracked = |path: &str| is_tracked(&fs, Path::new("/repo"), Path::new(path));
+        assert!(tracked("/repo/a/.npmrc"));
+        assert!(tracked("/repo/a/package.json"));
+        assert!(!tracked("/repo/package.json"));
+    }
+}
@@ -0,0 +1,204 @@
+//! External token helpers (`//host/:tokenHelper = /path/to/cmd`).
+//!
+//! A token helper is an executable that prints a registry token on stdout,
+//! as supported by older npm versions and pnpm. It is run without a shell or
+//! arguments, and its output is cached for the lifetime of the process.
+
+use crate::error::{Error, Result};
+use crate::secret_string::{wipe, SecretString};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{LazyLock, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Default time a token helper may run before it is killed.
+pub const DEFAULT_TOKEN_HELPER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often to check whether the helper has exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Tokens printed by helpers, keyed by helper path.
+static CACHE: LazyLock<Mutex<HashMap<PathBuf, SecretString>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Forget all cached token helper output.
+///
+/// The next credential lookup runs each helper again, e.g. after a token
+/// has been rotated.
+pub fn clear_token_helper_cache() {
+    CACHE.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// Get the token printed by a helper, running it if it isn't cached.
+pub(crate) fn token_from_helper(command: &str, timeout: Duration) -> Result<SecretString> {
+    let path = PathBuf::from(command);
+    let fail = |message: String| Error::TokenHelper {
+        command: path.clone(),
+        message,
+    };
+    if !path.is_absolute() {
+        return Err(fail("must be an absolute path".to_string()));
+    }
+
+    if let Some(token) = CACHE.lock().unwrap_or_else(|e| e.into_inner()).get(&path) {
+        return Ok(token.clone());
+    }
+
+    let token = run(&path, timeout).map_err(fail)?;
+    CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(path, token.clone());
+    Ok(token)
+}
+
+/// Run a helper and parse its stdout as a token.
+fn run(path: &Path, timeout: Duration) -> std::result::Result<SecretString, String> {
+    let mut child = Command::new(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to start: {}", e))?;
+
+    // Drain the pipes on separate threads so a chatty helper can't block.
+    // The output is sent back over a channel so reading it can time out too:
+    // a background process started by the helper may keep a pipe open.
+    let drain = |pipe: Option<Box<dyn Read + Send>>| {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            let _ = tx.send(buf);
+        });
+        rx
+    };
+    let stdout = drain(child.stdout.take().map(|p| Box::new(p) as _));
+    let stderr = drain(child.stderr.take().map(|p| Box::new(p) as _));
+
+    let deadline = Instant::now() + timeout;
+    let timed_out = || format!("timed out after {:?}", timeout);
+    let output = |rx: Receiver<Vec<u8>>| {
+        rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .map_err(|_| timed_out())
+    };
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(timed_out());
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(format!("failed to wait for exit: {}", e)),
+        }
+    };
+
+    if !status.success() {
+        let stderr = String::from_utf8_lossy(&output(stderr).unwrap_or_default()).into_owned();
+        let detail = stderr.lines().next().unwrap_or("").trim();
+        return Err(if detail.is_empty() {
+            format!("exited with {}", status)
+        } else {
+            format!("exited with {}: {}", status, detail)
+        });
+    }
+
+    let mut stdout =
+        String::from_utf8(output(stdout)?).map_err(|_| "printed invalid UTF-8".to_string())?;
+    let token = stdout.trim().to_string();
+    wipe(&mut stdout);
+    if token.is_empty() {
+        return Err("printed an empty token".to_string());
+    }
+    Ok(token.into())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    fn script(dir: &Path, name: &str, body: &str) -> String {
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_token_from_helper_is_cached() {
+        let temp = TempDir::new().unwrap();
+        let counter = temp.path().join("runs");
+        let helper = script(
+            temp.path(),
+            "helper.sh",
+            &format!(
+                "echo run >> '{}'\necho '  helper-token  '",
+                counter.display()
+            ),
+        );
+
+        let token = token_from_helper(&helper, DEFAULT_TOKEN_HELPER_TIMEOUT).unwrap();
+        assert_eq!(token.expose_secret(), "helper-token");
+        token_from_helper(&helper, DEFAULT_TOKEN_HELPER_TIMEOUT).unwrap();
+        assert_eq!(fs::read_to_string(&counter).unwrap().lines().count(), 1);
+    }
+
+    #[test]
+    fn test_token_helper_errors() {
+        let temp = TempDir::new().unwrap();
+        let timeout = Duration::from_secs(5);
+
+        let err = token_from_helper("relative/helper", timeout).unwrap_err();
+        assert!(err.to_string().contains("must be an absolute path"));
+
+        let failing = script(temp.path(), "fail.sh", "echo 'vault: denied' >&2\nexit 3");
+        let err = token_from_helper(&failing, timeout).unwrap_err();
+        assert!(err.to_string().contains("vault: denied"), "{}", err);
+
+        let empty = script(temp.path(), "empty.sh", "true");
+        let err = token_from_helper(&empty, timeout).unwrap_err();
+        assert!(err.to_string().contains("empty token"), "{}", err);
+
+        let missing = temp.path().join("missing.sh");
+        let err = token_from_helper(&missing.to_string_lossy(), timeout).unwrap_err();
+        assert!(err.to_string().contains("failed to start"), "{}", err);
+    }
+
+    #[test]
+    fn test_token_helper_timeout() {
+        let temp = TempDir::new().unwrap();
+        let slow = script(temp.path(), "slow.sh", "exec sleep 5");
+
+        let started = Instant::now();
+        let err = token_from_helper(&slow, Duration::from_millis(100)).unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_token_helper_timeout_with_background_child() {
+        let temp = TempDir::new().unwrap();
+        let forking = script(
+            temp.path(),
+            "fork.sh",
+            "sleep 60 &
+echo token",
+        );
+
+        let started = Instant::now();
+        let err = token_from_helper(&forking, Duration::from_millis(200)).unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+}
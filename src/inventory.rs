@@ -0,0 +1,216 @@
+//! Inventory of the registries referenced by a loaded config.
+//!
+//! Collects every registry that packages can be routed to or that has
+//! nerf-darted auth configured, so tools can audit which scopes use which
+//! registry and how each one authenticates.
+
+use crate::auth::{is_credential_key, nerf_dart, CredentialKind};
+use crate::config::NpmrcConfig;
+use std::collections::BTreeMap;
+use url::Url;
+
+/// Non-secret config fields that configure auth for a registry.
+const AUTH_CONFIG_FIELDS: &[&str] = &["certfile", "keyfile", "tokenHelper"];
+
+/// A registry referenced by the config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryInfo {
+    /// Registry URL. For registries only known from auth keys, this is the
+    /// nerf dart with an `https:` scheme.
+    pub url: Url,
+    /// Nerf-darted form of the registry, as used in auth keys.
+    pub nerf_dart: String,
+    /// Whether this is the default `registry`.
+    pub is_default: bool,
+    /// Scopes whose `@scope:registry` routes to this registry, sorted.
+    pub scopes: Vec<String>,
+    /// Kind of credentials configured for this registry, if any.
+    pub credential_kind: Option<CredentialKind>,
+}
+
+impl RegistryInfo {
+    /// Check whether any credentials are configured for this registry.
+    pub fn has_credentials(&self) -> bool {
+        self.credential_kind.is_some()
+    }
+}
+
+/// Split a nerf-darted key (`//host/path/:field`) into its nerf dart and field.
+pub(crate) fn split_nerf_dart_key(key: &str) -> Option<(&str, &str)> {
+    if !key.starts_with("//") {
+        return None;
+    }
+    // A trailing `:8080` is a port, not a field name.
+    key.rsplit_once(':').filter(|(nerf, field)| {
+        nerf.len() > 2 && !field.is_empty() && !field.bytes().all(|b| b.is_ascii_digit())
+    })
+}
+
+/// Iterate the nerf darts that have auth fields in any layer.
+pub(crate) fn auth_nerf_darts(config: &NpmrcConfig) -> impl Iterator<Item = &str> {
+    config
+        .layers()
+        .iter()
+        .flat_map(|layer| layer.data.data.keys())
+        .filter_map(|key| split_nerf_dart_key(key))
+        .filter(|(_, field)| is_credential_key(field) || AUTH_CONFIG_FIELDS.contains(field))
+        .map(|(nerf, _)| nerf)
+}
+
+/// Get or insert the inventory entry for a nerf dart.
+fn registry_entry(
+    registries: &mut BTreeMap<String, RegistryInfo>,
+    nerf: String,
+    url: Url,
+) -> &mut RegistryInfo {
+    registries
+        .entry(nerf.clone())
+        .or_insert_with(|| RegistryInfo {
+            url,
+            nerf_dart: nerf,
+            is_default: false,
+            scopes: Vec::new(),
+            credential_kind: None,
+        })
+}
+
+impl NpmrcConfig {
+    /// List every registry referenced by the config.
+    ///
+    /// Includes the default registry, every scoped registry and every
+    /// nerf-darted auth host in any layer, deduplicated by nerf dart. The
+    /// default registry comes first; the rest are sorted by nerf dart.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use npmrc_config_rs::NpmrcConfig;
+    ///
+    /// let config = NpmrcConfig::load()?;
+    /// for registry in config.registries() {
+    ///     println!(
+    ///         "{} scopes={:?} auth={}",
+    ///         registry.url,
+    ///         registry.scopes,
+    ///         registry.credential_kind.map_or("none", |k| k.as_str())
+    ///     );
+    /// }
+    /// # Ok::<(), npmrc_config_rs::Error>(())
+    /// ```
+    pub fn registries(&self) -> Vec<RegistryInfo> {
+        let default = self.default_registry();
+        let default_nerf = nerf_dart(&default);
+
+        let mut registries: BTreeMap<String, RegistryInfo> = BTreeMap::new();
+        registry_entry(&mut registries, default_nerf, default).is_default = true;
+
+        for (scope, url) in self.scoped_registries() {
+            registry_entry(&mut registries, nerf_dart(&url), url)
+                .scopes
+                .push(scope);
+        }
+
+        for nerf in auth_nerf_darts(self) {
+            if let Ok(url) = Url::parse(&format!("https:{}", nerf)) {
+                registry_entry(&mut registries, nerf.to_string(), url);
+            }
+        }
+
+        let mut result: Vec<RegistryInfo> = registries
+            .into_values()
+            .map(|mut info| {
+                info.scopes.sort();
+                // Token helpers are not run just to take inventory
+                info.credential_kind = if self.token_helper(&info.nerf_dart).is_some() {
+                    Some(CredentialKind::Token)
+                } else {
+                    self.credentials_for_nerf_dart(&info.nerf_dart)
+                        .map(|c| c.kind())
+                };
+                info
+            })
+            .collect();
+        result.sort_by_key(|info| !info.is_default);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConfigLayer, LoadOptions};
+    use crate::fs::MemoryFileSystem;
+    use std::sync::Arc;
+
+    fn load(npmrc: &str) -> NpmrcConfig {
+        let fs = MemoryFileSystem::new()
+            .with_file("/project/package.json", "{}")
+            .with_file("/project/.npmrc", npmrc);
+        NpmrcConfig::load_with_options(LoadOptions {
+            cwd: Some("/project".into()),
+            fs: Some(Arc::new(fs)),
+            skip_user: true,
+            skip_global: true,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_split_nerf_dart_key() {
+        assert_eq!(
+            split_nerf_dart_key("//host:8080/npm/:_authToken"),
+            Some(("//host:8080/npm/", "_authToken"))
+        );
+        assert_eq!(split_nerf_dart_key("@scope:registry"), None);
+        assert_eq!(split_nerf_dart_key("//host:8080"), None);
+    }
+
+    #[test]
+    fn test_registries_inventory() {
+        let mut config = load(
+            "registry = https://npm.corp.com/\n\
+             @a:registry = https://npm.corp.com/\n\
+             @b:registry = https://npm.corp.com\n\
+             @c:registry = https://other.com/npm/\n\
+             //npm.corp.com/:_authToken = token\n\
+             //orphan.com/:_auth = dXNlcjpwYXNz\n",
+        );
+        config.push_layer(
+            ConfigLayer::parse(
+                "ci",
+                ConfigLayer::PROJECT + 1,
+                "//certs.com:8443/:certfile = /c.pem\n//certs.com:8443/:keyfile = /k.pem\n",
+            )
+            .unwrap(),
+        );
+
+        let registries = config.registries();
+        let summary: Vec<_> = registries
+            .iter()
+            .map(|r| (r.nerf_dart.as_str(), r.is_default, r.credential_kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("//npm.corp.com/", true, Some(CredentialKind::Token)),
+                ("//certs.com:8443/", false, Some(CredentialKind::ClientCert)),
+                ("//orphan.com/", false, Some(CredentialKind::LegacyAuth)),
+                ("//other.com/npm/", false, None),
+            ]
+        );
+        assert_eq!(registries[0].scopes, vec!["@a", "@b"]);
+        assert_eq!(registries[3].scopes, vec!["@c"]);
+        assert!(!registries[3].has_credentials());
+        assert_eq!(registries[2].url.as_str(), "https://orphan.com/");
+    }
+
+    #[test]
+    fn test_registries_default_only() {
+        let registries = load("").registries();
+        assert_eq!(registries.len(), 1);
+        assert_eq!(registries[0].url.as_str(), "https://registry.npmjs.org/");
+        assert!(registries[0].is_default);
+        assert!(!registries[0].has_credentials());
+    }
+}
@@ -23,7 +23,7 @@
 //! if let Some(creds) = config.credentials_for(&registry) {
 //!     match creds {
 //!         npmrc_config_rs::Credentials::Token { token, .. } => {
-//!             println!("Using token: {}", token);
+//!             println!("Using token: {}", token.expose_secret());
 //!         }
 //!         npmrc_config_rs::Credentials::BasicAuth { username, password, .. } => {
 //!             println!("Using basic auth: {}:***", username);
@@ -64,17 +64,60 @@
 
 mod auth;
 mod config;
+#[cfg(feature = "serde")]
+mod de;
+mod defaults;
+mod env;
 mod error;
+mod expiry;
+mod fs;
+mod generate;
+mod gitignore;
+mod helper;
+mod inventory;
+mod lint;
+mod list;
+mod lockfile;
 mod parser;
 mod paths;
+mod permissions;
+mod publish;
+mod redact;
 pub mod registry;
+mod secret_string;
+mod secrets;
+#[cfg(feature = "serde")]
+mod ser;
+mod spec;
+mod token;
 
 // Re-export main types
-pub use auth::{nerf_dart, ClientCert, Credentials};
-pub use config::{ConfigData, LoadOptions, NpmrcConfig};
+pub use auth::{is_credential_key, nerf_dart, AuthType, ClientCert, CredentialKind, Credentials};
+pub use config::{ConfigData, ConfigLayer, LoadOptions, Location, NpmrcConfig, UnresolvedVar};
+#[cfg(feature = "serde")]
+pub use de::ConfigDeserializer;
+pub use env::npm_config_env_key;
 pub use error::{Error, Result};
+pub use expiry::TokenReport;
+pub use fs::{FilePermissions, FileSystem, MemoryFileSystem, RealFileSystem};
+pub use generate::MinimalNpmrc;
+pub use helper::{clear_token_helper_cache, DEFAULT_TOKEN_HELPER_TIMEOUT};
+pub use inventory::RegistryInfo;
+pub use lint::{LintIssue, NerfDartMismatch};
+pub use list::{ConfigListing, ListedEntry, ListedLevel};
+pub use lockfile::{LockfileCoverage, LockfileIssue, ResolvedGroup};
 pub use parser::{expand_env_vars, parse_bool};
 pub use paths::{
-    expand_tilde, find_global_prefix, find_local_prefix, global_config_path, project_config_path,
-    user_config_path,
+    discover_local_prefix, expand_tilde, expand_tilde_with, find_global_prefix, find_local_prefix,
+    find_local_prefix_in, global_config_path, project_config_path, user_config_path,
+    user_config_path_in, LocalPrefix,
 };
+pub use permissions::{PermissionIssue, PermissionProblem, SECURE_MODE};
+pub use publish::PublishTarget;
+pub use redact::redact;
+pub use secret_string::SecretString;
+pub use secrets::{GitIgnoreStatus, SecretFinding, SecretScan};
+#[cfg(feature = "serde")]
+pub use ser::{RedactedConfig, RedactedConfigData, Redaction};
+pub use spec::{parse_spec, PackageSpec, ResolvedSpec, SpecType};
+pub use token::{classify_token, JwtClaims, TokenExpiry, TokenInfo, TokenKind};
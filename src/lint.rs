@@ -0,0 +1,298 @@
+//! Cross-checks between configured registries and nerf-darted credentials.
+//!
+//! Credentials are looked up by the registry's nerf dart and then its parent
+//! paths on the same host and port, so an auth key with a different port or a
+//! path that isn't a parent of the registry's is silently ignored. These
+//! checks surface such mistakes along with unused credentials and scopes that
+//! have no auth.
+
+use crate::config::NpmrcConfig;
+use crate::inventory::RegistryInfo;
+use std::collections::BTreeSet;
+use std::fmt;
+use url::Url;
+
+/// Which part of a nerf dart differs from the registry it nearly matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NerfDartMismatch {
+    /// Same host, different port.
+    Port,
+    /// Same host and port, different path (including a missing trailing slash).
+    Path,
+}
+
+/// A problem found by [`NpmrcConfig::lint`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintIssue {
+    /// Credentials for a registry that neither the default registry nor any
+    /// scope routes to.
+    OrphanedAuth {
+        /// Nerf dart the credentials are configured under.
+        nerf_dart: String,
+    },
+    /// A scope routes to a registry with no credentials configured.
+    ScopeWithoutAuth {
+        /// The scope, e.g. `@myorg`.
+        scope: String,
+        /// The registry the scope routes to.
+        registry: Url,
+    },
+    /// Credentials whose nerf dart is close to, but never matches, a
+    /// registry in use.
+    NearMissAuth {
+        /// Nerf dart the credentials are configured under.
+        nerf_dart: String,
+        /// The registry the credentials were probably meant for.
+        registry: Url,
+        /// Nerf dart the credentials should use.
+        expected: String,
+        /// Which part differs.
+        mismatch: NerfDartMismatch,
+    },
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintIssue::OrphanedAuth { nerf_dart } => write!(
+                f,
+                "credentials for '{}' are not used by the default registry or any scope",
+                nerf_dart
+            ),
+            LintIssue::ScopeWithoutAuth { scope, registry } => write!(
+                f,
+                "scope '{}' uses {} but no credentials are configured for it",
+                scope, registry
+            ),
+            LintIssue::NearMissAuth {
+                nerf_dart,
+                registry,
+                expected,
+                mismatch,
+            } => {
+                let part = match mismatch {
+                    NerfDartMismatch::Port => "port",
+                    NerfDartMismatch::Path => "path",
+                };
+                write!(
+                    f,
+                    "credentials for '{}' never match {} ({} differs, expected '{}')",
+                    nerf_dart, registry, part, expected
+                )
+            }
+        }
+    }
+}
+
+/// Check whether a registry is routed to by the default registry or a scope.
+fn is_used(info: &RegistryInfo) -> bool {
+    info.is_default || !info.scopes.is_empty()
+}
+
+/// Compare an auth nerf dart against a registry in use.
+fn near_miss(auth: &Url, used: &Url) -> Option<NerfDartMismatch> {
+    if auth.host_str() != used.host_str() {
+        return None;
+    }
+    // Compare ports as they appear in nerf darts, which omit scheme defaults.
+    if auth.port() != used.port() {
+        return Some(NerfDartMismatch::Port);
+    }
+    if auth.path() != used.path() {
+        return Some(NerfDartMismatch::Path);
+    }
+    None
+}
+
+impl NpmrcConfig {
+    /// Cross-check nerf-darted credentials against the registries in use.
+    ///
+    /// Reports credentials no registry uses, credentials that nearly match a
+    /// registry in use but differ in port or path (and so never match in
+    /// [`NpmrcConfig::credentials_for`]), and scopes whose registry has no
+    /// credentials. Credentials on a parent path of a registry in use, like
+    /// `//npm.corp.com/` for `https://npm.corp.com/api/npm/`, count as used
+    /// when no closer nerf dart has credentials, since lookups fall back to
+    /// them.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use npmrc_config_rs::NpmrcConfig;
+    ///
+    /// let config = NpmrcConfig::load()?;
+    /// for issue in config.lint() {
+    ///     eprintln!("warning: {}", issue);
+    /// }
+    /// # Ok::<(), npmrc_config_rs::Error>(())
+    /// ```
+    pub fn lint(&self) -> Vec<LintIssue> {
+        let registries = self.registries();
+        let (used, unused): (Vec<_>, Vec<_>) = registries.iter().partition(|r| is_used(r));
+        // Nerf darts whose credentials a lookup for a registry in use picks
+        let looked_up: BTreeSet<String> = used
+            .iter()
+            .filter_map(|r| self.own_auth_nerf_dart(&r.url))
+            .collect();
+        let mut issues = Vec::new();
+
+        for auth in unused
+            .into_iter()
+            .filter(|r| !looked_up.contains(&r.nerf_dart))
+        {
+            let miss = used
+                .iter()
+                .find_map(|r| near_miss(&auth.url, &r.url).map(|m| (r, m)));
+            issues.push(match miss {
+                Some((registry, mismatch)) => LintIssue::NearMissAuth {
+                    nerf_dart: auth.nerf_dart.clone(),
+                    registry: registry.url.clone(),
+                    expected: registry.nerf_dart.clone(),
+                    mismatch,
+                },
+                None => LintIssue::OrphanedAuth {
+                    nerf_dart: auth.nerf_dart.clone(),
+                },
+            });
+        }
+
+        for registry in used
+            .iter()
+            .filter(|r| self.own_auth_nerf_dart(&r.url).is_none())
+        {
+            for scope in &registry.scopes {
+                issues.push(LintIssue::ScopeWithoutAuth {
+                    scope: scope.clone(),
+                    registry: registry.url.clone(),
+                });
+            }
+        }
+
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LoadOptions;
+    use crate::fs::MemoryFileSystem;
+    use std::sync::Arc;
+
+    fn load(npmrc: &str) -> NpmrcConfig {
+        let fs = MemoryFileSystem::new()
+            .with_file("/project/package.json", "{}")
+            .with_file("/project/.npmrc", npmrc);
+        NpmrcConfig::load_with_options(LoadOptions {
+            cwd: Some("/project".into()),
+            fs: Some(Arc::new(fs)),
+            skip_user: true,
+            skip_global: true,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_lint_clean_config() {
+        let config = load(
+            "@a:registry = https://npm.corp.com/\n\
+             //npm.corp.com/:_authToken = token\n",
+        );
+        assert!(config.lint().is_empty());
+    }
+
+    #[test]
+    fn test_lint_orphaned_auth_and_scope_without_auth() {
+        let config = load(
+            "@a:registry = https://npm.corp.com/\n\
+             //old.corp.com/:_authToken = token\n",
+        );
+        assert_eq!(
+            config.lint(),
+            vec![
+                LintIssue::OrphanedAuth {
+                    nerf_dart: "//old.corp.com/".to_string()
+                },
+                LintIssue::ScopeWithoutAuth {
+                    scope: "@a".to_string(),
+                    registry: Url::parse("https://npm.corp.com/").unwrap(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_near_miss_path() {
+        let config = load(
+            "registry = https://npm.corp.com/api/npm/\n\
+             //npm.corp.com/api/npm:_authToken = token\n",
+        );
+        let issues = config.lint();
+        assert_eq!(
+            issues,
+            vec![LintIssue::NearMissAuth {
+                nerf_dart: "//npm.corp.com/api/npm".to_string(),
+                registry: Url::parse("https://npm.corp.com/api/npm/").unwrap(),
+                expected: "//npm.corp.com/api/npm/".to_string(),
+                mismatch: NerfDartMismatch::Path,
+            }]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "credentials for '//npm.corp.com/api/npm' never match \
+             https://npm.corp.com/api/npm/ (path differs, expected '//npm.corp.com/api/npm/')"
+        );
+    }
+
+    #[test]
+    fn test_lint_parent_path_auth_is_used() {
+        let config = load(
+            "registry = https://npm.corp.com/api/npm/\n\
+             @a:registry = https://npm.corp.com/api/other/\n\
+             //npm.corp.com/:_authToken = token\n",
+        );
+        assert!(config.lint().is_empty(), "{:?}", config.lint());
+
+        // A closer nerf dart wins, so the parent's credentials are unused
+        let config = load(
+            "registry = https://npm.corp.com/api/npm/\n\
+             //npm.corp.com/api/npm/:_authToken = token\n\
+             //npm.corp.com/:_authToken = token\n",
+        );
+        assert_eq!(
+            config.lint(),
+            vec![LintIssue::NearMissAuth {
+                nerf_dart: "//npm.corp.com/".to_string(),
+                registry: Url::parse("https://npm.corp.com/api/npm/").unwrap(),
+                expected: "//npm.corp.com/api/npm/".to_string(),
+                mismatch: NerfDartMismatch::Path,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_lint_near_miss_port() {
+        let config = load(
+            "@a:registry = https://npm.corp.com:8443/\n\
+             //npm.corp.com:8443/:_authToken = token\n\
+             @b:registry = http://localhost:4873/\n\
+             //localhost/:_authToken = token\n",
+        );
+        assert_eq!(
+            config.lint(),
+            vec![
+                LintIssue::NearMissAuth {
+                    nerf_dart: "//localhost/".to_string(),
+                    registry: Url::parse("http://localhost:4873/").unwrap(),
+                    expected: "//localhost:4873/".to_string(),
+                    mismatch: NerfDartMismatch::Port,
+                },
+                LintIssue::ScopeWithoutAuth {
+                    scope: "@b".to_string(),
+                    registry: Url::parse("http://localhost:4873/").unwrap(),
+                },
+            ]
+        );
+    }
+}
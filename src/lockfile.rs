@@ -0,0 +1,439 @@
+//! Lockfile parsing and credential coverage checks.
+//!
+//! Supports `package-lock.json` and `npm-shrinkwrap.json` in lockfile
+//! versions 1 (nested `dependencies`), 2 and 3 (flat `packages`).
+
+use crate::auth::nerf_dart;
+use crate::config::{host_nerf_dart, NpmrcConfig};
+use crate::error::{Error, Result};
+use crate::fs::FileSystem;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// Public registries that serve packages without auth.
+const PUBLIC_HOSTS: &[&str] = &["registry.npmjs.org", "registry.yarnpkg.com"];
+
+/// A package entry from a lockfile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LockedPackage {
+    /// Real package name (alias targets are resolved).
+    pub name: String,
+    /// The `resolved` URL or path, if recorded.
+    pub resolved: Option<String>,
+}
+
+/// Read every installed package from a lockfile.
+///
+/// The root project, workspace links and packages outside `node_modules`
+/// are left out.
+pub(crate) fn read_lockfile(fs: &dyn FileSystem, path: &Path) -> Result<Vec<LockedPackage>> {
+    let content = fs.read_to_string(path).map_err(|e| Error::ReadFile {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    let invalid = |message: String| Error::ParseLockfile {
+        path: path.to_path_buf(),
+        message,
+    };
+    let lockfile: Value = serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+    let lockfile = lockfile
+        .as_object()
+        .ok_or_else(|| invalid("expected a JSON object".to_string()))?;
+
+    let mut packages = Vec::new();
+    // v2 lockfiles carry both forms; `packages` is authoritative
+    if let Some(entries) = lockfile.get("packages").and_then(Value::as_object) {
+        collect_packages(entries, &mut packages);
+    } else if let Some(deps) = lockfile.get("dependencies").and_then(Value::as_object) {
+        collect_dependencies(deps, &mut packages);
+    } else {
+        return Err(invalid(
+            "no \"packages\" or \"dependencies\" section".to_string(),
+        ));
+    }
+    Ok(packages)
+}
+
+/// Collect entries from a v2/v3 `packages` map keyed by install path.
+fn collect_packages(entries: &Map<String, Value>, packages: &mut Vec<LockedPackage>) {
+    for (path, entry) in entries {
+        let Some((_, installed_as)) = path.rsplit_once("node_modules/") else {
+            continue;
+        };
+        if entry.get("link").and_then(Value::as_bool) == Some(true) {
+            continue;
+        }
+        let name = entry
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or(installed_as);
+        packages.push(LockedPackage {
+            name: name.to_string(),
+            resolved: resolved(entry),
+        });
+    }
+}
+
+/// Collect entries from a v1 nested `dependencies` tree.
+fn collect_dependencies(deps: &Map<String, Value>, packages: &mut Vec<LockedPackage>) {
+    for (installed_as, entry) in deps {
+        // Aliases record `"version": "npm:real-name@1.2.3"`
+        let name = entry
+            .get("version")
+            .and_then(Value::as_str)
+            .and_then(|v| v.strip_prefix("npm:"))
+            .and_then(|spec| spec.rsplit_once('@'))
+            .map(|(name, _)| name)
+            .filter(|name| !name.is_empty())
+            .unwrap_or(installed_as);
+        packages.push(LockedPackage {
+            name: name.to_string(),
+            resolved: resolved(entry),
+        });
+        if let Some(nested) = entry.get("dependencies").and_then(Value::as_object) {
+            collect_dependencies(nested, packages);
+        }
+    }
+}
+
+/// Get an entry's `resolved` field.
+fn resolved(entry: &Value) -> Option<String> {
+    entry
+        .get("resolved")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// Resolved URLs from a lockfile that fall under one registry or host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedGroup {
+    /// Nerf dart of the configured registry the URLs fall under, or of their
+    /// host (`//host[:port]/`) if no configured registry matches.
+    pub nerf_dart: String,
+    /// Whether the nerf dart belongs to a registry in the config.
+    pub known_registry: bool,
+    /// Whether the host is a public registry that needs no auth.
+    pub public: bool,
+    /// Whether credentials match every URL in the group.
+    pub has_credentials: bool,
+    /// Names of the packages resolved here, sorted and deduplicated.
+    pub packages: Vec<String>,
+}
+
+/// A problem found by [`NpmrcConfig::check_lockfile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockfileIssue {
+    /// Packages resolve to a private host without matching credentials.
+    MissingCredentials {
+        /// Nerf dart of the group.
+        nerf_dart: String,
+        /// Number of packages resolved there.
+        packages: usize,
+    },
+    /// Packages resolve to a host that no registry in the config points to.
+    UnknownRegistry {
+        /// Nerf dart of the host.
+        nerf_dart: String,
+        /// Number of packages resolved there.
+        packages: usize,
+    },
+}
+
+impl fmt::Display for LockfileIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockfileIssue::MissingCredentials {
+                nerf_dart,
+                packages,
+            } => write!(
+                f,
+                "{} package(s) resolve to '{}' but no credentials match it",
+                packages, nerf_dart
+            ),
+            LockfileIssue::UnknownRegistry {
+                nerf_dart,
+                packages,
+            } => write!(
+                f,
+                "{} package(s) resolve to '{}', which is not a configured registry",
+                packages, nerf_dart
+            ),
+        }
+    }
+}
+
+/// Result of [`NpmrcConfig::check_lockfile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockfileCoverage {
+    /// The lockfile that was checked.
+    pub lockfile: PathBuf,
+    /// Resolved URLs grouped by nerf dart, sorted by nerf dart.
+    pub groups: Vec<ResolvedGroup>,
+}
+
+impl LockfileCoverage {
+    /// List the problems found.
+    ///
+    /// Public registries are never reported as missing credentials.
+    pub fn issues(&self) -> Vec<LockfileIssue> {
+        let mut issues = Vec::new();
+        for group in &self.groups {
+            if !group.known_registry {
+                issues.push(LockfileIssue::UnknownRegistry {
+                    nerf_dart: group.nerf_dart.clone(),
+                    packages: group.packages.len(),
+                });
+            }
+            if !group.has_credentials && !group.public {
+                issues.push(LockfileIssue::MissingCredentials {
+                    nerf_dart: group.nerf_dart.clone(),
+                    packages: group.packages.len(),
+                });
+            }
+        }
+        issues
+    }
+}
+
+impl NpmrcConfig {
+    /// Check that every `resolved` URL in a lockfile is covered by the config.
+    ///
+    /// Reads a `package-lock.json` or `npm-shrinkwrap.json` (v1, v2 or v3)
+    /// through [`NpmrcConfig::fs`]. Each `http(s)` URL is first rewritten
+    /// with [`NpmrcConfig::rewrite_resolved_url`], as npm would, then grouped
+    /// under the longest matching registry from [`NpmrcConfig::registries`],
+    /// or its host if none matches. A URL counts as having credentials if
+    /// they are configured under its nerf dart or any parent path on its
+    /// host. Git, file and link entries are skipped, and token helpers are
+    /// not run.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ReadFile`] if the lockfile can't be read and
+    /// [`Error::ParseLockfile`] if it is not a lockfile.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use npmrc_config_rs::NpmrcConfig;
+    ///
+    /// let config = NpmrcConfig::load()?;
+    /// let coverage = config.check_lockfile("package-lock.json")?;
+    /// for issue in coverage.issues() {
+    ///     eprintln!("error: {}", issue);
+    /// }
+    /// # Ok::<(), npmrc_config_rs::Error>(())
+    /// ```
+    pub fn check_lockfile(&self, lockfile: impl AsRef<Path>) -> Result<LockfileCoverage> {
+        let path = lockfile.as_ref();
+        let registries: Vec<String> = self
+            .registries()
+            .into_iter()
+            .map(|registry| registry.nerf_dart)
+            .collect();
+
+        let mut groups: BTreeMap<String, ResolvedGroup> = BTreeMap::new();
+        for package in read_lockfile(self.fs(), path)? {
+            let Some(url) = package.resolved.and_then(|r| Url::parse(&r).ok()) else {
+                continue;
+            };
+            if !matches!(url.scheme(), "http" | "https") {
+                continue;
+            }
+            let url = self.rewrite_resolved_url(&url);
+            let url_nerf = nerf_dart(&url);
+
+            let registry = registries
+                .iter()
+                .filter(|nerf| url_nerf.starts_with(nerf.as_str()))
+                .max_by_key(|nerf| nerf.len());
+            let nerf_dart = registry.cloned().unwrap_or_else(|| host_nerf_dart(&url));
+            let has_credentials = self.own_auth_nerf_dart(&url).is_some();
+
+            let group = groups
+                .entry(nerf_dart.clone())
+                .or_insert_with(|| ResolvedGroup {
+                    nerf_dart,
+                    known_registry: registry.is_some(),
+                    public: url
+                        .host_str()
+                        .is_some_and(|host| PUBLIC_HOSTS.contains(&host)),
+                    has_credentials: true,
+                    packages: Vec::new(),
+                });
+            group.has_credentials &= has_credentials;
+            group.packages.push(package.name);
+        }
+
+        let groups = groups
+            .into_values()
+            .map(|mut group| {
+                group.packages.sort();
+                group.packages.dedup();
+                group
+            })
+            .collect();
+        Ok(LockfileCoverage {
+            lockfile: path.to_path_buf(),
+            groups,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConfigLayer, LoadOptions};
+    use crate::fs::MemoryFileSystem;
+    use std::sync::Arc;
+
+    const V1: &str = r#"{
+        "lockfileVersion": 1,
+        "dependencies": {
+            "lodash": {"version": "4.17.21", "resolved": "https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz"},
+            "@corp/ui": {
+                "version": "1.0.0",
+                "resolved": "https://npm.corp.com/npm/@corp/ui/-/ui-1.0.0.tgz",
+                "dependencies": {
+                    "@corp/core": {"version": "2.0.0", "resolved": "https://npm.corp.com/npm/@corp/core/-/core-2.0.0.tgz"}
+                }
+            },
+            "old": {"version": "npm:legacy-pkg@1.0.0", "resolved": "https://legacy.com/legacy-pkg-1.0.0.tgz"},
+            "from-git": {"version": "git+ssh://git@github.com/o/r.git#abc", "resolved": "git+ssh://git@github.com/o/r.git#abc"}
+        }
+    }"#;
+
+    const V3: &str = r#"{
+        "lockfileVersion": 3,
+        "packages": {
+            "": {"name": "app"},
+            "node_modules/lodash": {"resolved": "https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz"},
+            "node_modules/@corp/ui": {"resolved": "https://npm.corp.com/npm/@corp/ui/-/ui-1.0.0.tgz"},
+            "node_modules/@corp/ui/node_modules/@corp/core": {"resolved": "https://npm.corp.com/npm/@corp/core/-/core-2.0.0.tgz"},
+            "node_modules/old": {"name": "legacy-pkg", "resolved": "https://legacy.com/legacy-pkg-1.0.0.tgz"},
+            "node_modules/ws": {"link": true, "resolved": "packages/ws"},
+            "packages/ws": {"name": "ws"}
+        }
+    }"#;
+
+    fn load(lockfile: &str) -> NpmrcConfig {
+        let fs = MemoryFileSystem::new()
+            .with_file("/project/package.json", "{}")
+            .with_file("/project/package-lock.json", lockfile)
+            .with_file(
+                "/project/.npmrc",
+                "@corp:registry = https://npm.corp.com/npm/\n",
+            );
+        NpmrcConfig::load_with_options(LoadOptions {
+            cwd: Some("/project".into()),
+            fs: Some(Arc::new(fs)),
+            skip_user: true,
+            skip_global: true,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_read_lockfile_versions() {
+        let cases = [
+            (
+                V1,
+                vec!["@corp/core", "@corp/ui", "from-git", "legacy-pkg", "lodash"],
+            ),
+            (V3, vec!["@corp/core", "@corp/ui", "legacy-pkg", "lodash"]),
+        ];
+        for (lockfile, expected) in cases {
+            let config = load(lockfile);
+            let path = Path::new("/project/package-lock.json");
+            let mut names: Vec<_> = read_lockfile(config.fs(), path)
+                .unwrap()
+                .into_iter()
+                .map(|p| p.name)
+                .collect();
+            names.sort();
+            assert_eq!(names, expected);
+        }
+    }
+
+    #[test]
+    fn test_check_lockfile() {
+        for lockfile in [V1, V3] {
+            let coverage = load(lockfile)
+                .check_lockfile("/project/package-lock.json")
+                .unwrap();
+            let summary: Vec<_> = coverage
+                .groups
+                .iter()
+                .map(|g| {
+                    (
+                        g.nerf_dart.as_str(),
+                        g.known_registry,
+                        g.has_credentials,
+                        g.packages.len(),
+                    )
+                })
+                .collect();
+            assert_eq!(
+                summary,
+                vec![
+                    ("//legacy.com/", false, false, 1),
+                    ("//npm.corp.com/npm/", true, false, 2),
+                    ("//registry.npmjs.org/", true, false, 1),
+                ]
+            );
+
+            let issues: Vec<_> = coverage.issues().iter().map(|i| i.to_string()).collect();
+            assert_eq!(
+                issues,
+                vec![
+                    "1 package(s) resolve to '//legacy.com/', which is not a configured registry",
+                    "1 package(s) resolve to '//legacy.com/' but no credentials match it",
+                    "2 package(s) resolve to '//npm.corp.com/npm/' but no credentials match it",
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_lockfile_with_credentials_and_rewrite() {
+        let mut config = load(V3);
+        config.push_layer(
+            ConfigLayer::parse(
+                "ci",
+                ConfigLayer::PROJECT + 1,
+                "registry = https://mirror.corp.com/\n\
+                 //mirror.corp.com/:_authToken = t\n\
+                 //npm.corp.com/:_authToken = t\n\
+                 //legacy.com/:_authToken = t\n",
+            )
+            .unwrap(),
+        );
+        let coverage = config.check_lockfile("/project/package-lock.json").unwrap();
+        // registry.npmjs.org URLs are rewritten to the mirror
+        let nerfs: Vec<_> = coverage
+            .groups
+            .iter()
+            .map(|g| g.nerf_dart.as_str())
+            .collect();
+        assert_eq!(
+            nerfs,
+            vec!["//legacy.com/", "//mirror.corp.com/", "//npm.corp.com/npm/"]
+        );
+        assert!(coverage.groups.iter().all(|g| g.has_credentials));
+        // legacy.com is known through its auth key
+        assert!(coverage.issues().is_empty());
+    }
+
+    #[test]
+    fn test_check_lockfile_invalid() {
+        let config = load(r#"{"name": "app"}"#);
+        assert!(matches!(
+            config.check_lockfile("/project/package-lock.json"),
+            Err(Error::ParseLockfile { .. })
+        ));
+    }
+}
@@ -19,6 +19,17 @@ use std::sync::LazyLock;
 static ENV_EXPR: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?P<esc>\\*)\$\{(?P<name>[^${}?]+)(?P<mod>\?)?\}").unwrap());
 
+/// Result of parsing a single .npmrc file.
+#[derive(Debug, Default)]
+pub(crate) struct ParsedNpmrc {
+    /// Key-value pairs with environment variables expanded.
+    pub data: HashMap<String, String>,
+    /// `(key, variable)` pairs for every `${VAR}` reference left unexpanded.
+    pub unresolved: Vec<(String, String)>,
+    /// Values as written, for keys whose value contains a `${VAR}` reference.
+    pub unexpanded: HashMap<String, String>,
+}
+
 /// Parse .npmrc INI content into key-value pairs.
 ///
 /// The parser handles:
@@ -30,39 +41,73 @@ static ENV_EXPR: LazyLock<Regex> =
 /// Unlike standard INI files, .npmrc files:
 /// - Don't use sections (no `[section]` headers)
 /// - Allow keys starting with special characters like `@` and `//`
-pub fn parse_npmrc(content: &str, _path: &Path) -> Result<HashMap<String, String>> {
+///
+/// `${VAR}` references that cannot be resolved are kept literally and
+/// reported in [`ParsedNpmrc::unresolved`].
+pub(crate) fn parse_npmrc(content: &str, _path: &Path) -> Result<ParsedNpmrc> {
     let mut result = HashMap::new();
+    let mut unresolved = Vec::new();
+    let mut unexpanded = HashMap::new();
+
+    for RawEntry { key, value, .. } in raw_entries(content) {
+        let mut missing = Vec::new();
+        let expanded = expand_env_vars_tracked(value, &mut missing);
+        // A later duplicate key replaces the earlier value entirely
+        unresolved.retain(|(k, _): &(String, String)| k != key);
+        unresolved.extend(missing.into_iter().map(|var| (key.to_string(), var)));
+        if value.contains("${") {
+            unexpanded.insert(key.to_string(), value.to_string());
+        } else {
+            unexpanded.remove(key);
+        }
+        result.insert(key.to_string(), expanded);
+    }
 
-    for line in content.lines() {
-        let line = line.trim();
+    Ok(ParsedNpmrc {
+        data: result,
+        unresolved,
+        unexpanded,
+    })
+}
 
-        // Skip empty lines
-        if line.is_empty() {
-            continue;
-        }
+/// A `key = value` line from .npmrc content, before env expansion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RawEntry<'a> {
+    /// 1-based line number.
+    pub line: usize,
+    pub key: &'a str,
+    pub value: &'a str,
+}
+
+/// Iterate the `key = value` lines of .npmrc content in file order.
+///
+/// Comments, blank lines, lines without `=` and empty keys are skipped.
+/// Duplicate keys are all returned.
+pub(crate) fn raw_entries(content: &str) -> impl Iterator<Item = RawEntry<'_>> {
+    content.lines().enumerate().filter_map(|(idx, line)| {
+        let line = line.trim();
 
-        // Skip comments (lines starting with # or ;)
-        if line.starts_with('#') || line.starts_with(';') {
-            continue;
+        // Skip empty lines and comments (lines starting with # or ;)
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            return None;
         }
 
         // Parse key=value or key = value
-        if let Some(eq_pos) = line.find('=') {
-            let key = line[..eq_pos].trim();
-            let value = line[eq_pos + 1..].trim();
-
-            // Skip empty keys
-            if key.is_empty() {
-                continue;
-            }
+        // Lines without = are ignored (npm's ini parser also ignores them)
+        let (key, value) = line.split_once('=')?;
+        let key = key.trim();
 
-            let expanded = expand_env_vars(value);
-            result.insert(key.to_string(), expanded);
+        // Skip empty keys
+        if key.is_empty() {
+            return None;
         }
-        // Lines without = are ignored (npm's ini parser also ignores them)
-    }
 
-    Ok(result)
+        Some(RawEntry {
+            line: idx + 1,
+            key,
+            value: value.trim(),
+        })
+    })
 }
 
 /// Expand `${VAR}` environment variable references in a value.
@@ -71,6 +116,14 @@ pub fn parse_npmrc(content: &str, _path: &Path) -> Result<HashMap<String, String
 /// - `${VAR?}` - Expands to the value of VAR, or empty string if undefined
 /// - `\\${VAR}` - Escaped, keeps the literal (with one less backslash)
 pub fn expand_env_vars(value: &str) -> String {
+    expand_env_vars_tracked(value, &mut Vec::new())
+}
+
+/// Expand `${VAR}` references like [`expand_env_vars`], pushing the name of
+/// every variable that was left as a literal because it is undefined.
+///
+/// Optional references (`${VAR?}`) and escaped references are never reported.
+pub(crate) fn expand_env_vars_tracked(value: &str, unresolved: &mut Vec<String>) -> String {
     ENV_EXPR
         .replace_all(value, |caps: &regex::Captures| {
             let esc = caps.name("esc").map_or("", |m| m.as_str());
@@ -96,6 +149,7 @@ pub fn expand_env_vars(value: &str) -> String {
                     if modifier == "?" {
                         String::new()
                     } else {
+                        unresolved.push(name.to_string());
                         format!("${{{}}}", name)
                     }
                 }
@@ -127,7 +181,7 @@ mod tests {
 registry = https://registry.npmjs.org/
 strict-ssl = true
 "#;
-        let result = parse_npmrc(content, Path::new("test")).unwrap();
+        let result = parse_npmrc(content, Path::new("test")).unwrap().data;
         assert_eq!(
             result.get("registry"),
             Some(&"https://registry.npmjs.org/".to_string())
@@ -140,7 +194,7 @@ strict-ssl = true
         let content = r#"
 @myorg:registry = https://registry.mycorp.com/
 "#;
-        let result = parse_npmrc(content, Path::new("test")).unwrap();
+        let result = parse_npmrc(content, Path::new("test")).unwrap().data;
         assert_eq!(
             result.get("@myorg:registry"),
             Some(&"https://registry.mycorp.com/".to_string())
@@ -154,7 +208,7 @@ strict-ssl = true
 //registry.mycorp.com/:username = myuser
 //registry.mycorp.com/:_password = cGFzc3dvcmQ=
 "#;
-        let result = parse_npmrc(content, Path::new("test")).unwrap();
+        let result = parse_npmrc(content, Path::new("test")).unwrap().data;
         assert_eq!(
             result.get("//registry.npmjs.org/:_authToken"),
             Some(&"token123".to_string())
@@ -176,7 +230,7 @@ strict-ssl = true
 ; This is also a comment
 registry = https://registry.npmjs.org/
 "#;
-        let result = parse_npmrc(content, Path::new("test")).unwrap();
+        let result = parse_npmrc(content, Path::new("test")).unwrap().data;
         assert_eq!(result.len(), 1);
         assert_eq!(
             result.get("registry"),
@@ -187,7 +241,7 @@ registry = https://registry.npmjs.org/
     #[test]
     fn test_parse_no_spaces() {
         let content = "registry=https://registry.npmjs.org/";
-        let result = parse_npmrc(content, Path::new("test")).unwrap();
+        let result = parse_npmrc(content, Path::new("test")).unwrap().data;
         assert_eq!(
             result.get("registry"),
             Some(&"https://registry.npmjs.org/".to_string())
@@ -197,7 +251,7 @@ registry = https://registry.npmjs.org/
     #[test]
     fn test_parse_value_with_equals() {
         let content = "key = value=with=equals";
-        let result = parse_npmrc(content, Path::new("test")).unwrap();
+        let result = parse_npmrc(content, Path::new("test")).unwrap().data;
         assert_eq!(result.get("key"), Some(&"value=with=equals".to_string()));
     }
 
@@ -238,6 +292,74 @@ registry = https://registry.npmjs.org/
         std::env::remove_var("TEST_VAR2");
     }
 
+    #[test]
+    fn test_parse_tracks_unresolved_vars() {
+        std::env::remove_var("PARSER_UNSET_TOKEN");
+        std::env::remove_var("PARSER_UNSET_OPTIONAL");
+
+        let content = r#"
+//registry.npmjs.org/:_authToken = ${PARSER_UNSET_TOKEN}
+//other.example.com/:_authToken = ${PARSER_UNSET_OPTIONAL?}
+escaped = \${PARSER_UNSET_TOKEN}
+"#;
+        let parsed = parse_npmrc(content, Path::new("test")).unwrap();
+        assert_eq!(
+            parsed.data.get("//registry.npmjs.org/:_authToken"),
+            Some(&"${PARSER_UNSET_TOKEN}".to_string())
+        );
+        assert_eq!(
+            parsed.unresolved,
+            vec![(
+                "//registry.npmjs.org/:_authToken".to_string(),
+                "PARSER_UNSET_TOKEN".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_parse_duplicate_key_clears_unresolved() {
+        std::env::remove_var("PARSER_UNSET_DUP");
+
+        let content = "token = ${PARSER_UNSET_DUP}\ntoken = literal\n";
+        let parsed = parse_npmrc(content, Path::new("test")).unwrap();
+        assert!(parsed.unresolved.is_empty());
+        assert!(parsed.unexpanded.is_empty());
+    }
+
+    #[test]
+    fn test_parse_keeps_unexpanded_values() {
+        std::env::set_var("PARSER_SET_TOKEN", "secret");
+
+        let content = "token = ${PARSER_SET_TOKEN}\nplain = value\n";
+        let parsed = parse_npmrc(content, Path::new("test")).unwrap();
+        assert_eq!(parsed.data["token"], "secret");
+        assert_eq!(
+            parsed.unexpanded,
+            HashMap::from([("token".to_string(), "${PARSER_SET_TOKEN}".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_raw_entries_keep_lines_and_literals() {
+        let content = "# comment\n\ntoken = ${TOKEN}\nno-equals\ntoken=second\n";
+        let entries: Vec<_> = raw_entries(content).collect();
+        assert_eq!(
+            entries,
+            vec![
+                RawEntry {
+                    line: 3,
+                    key: "token",
+                    value: "${TOKEN}"
+                },
+                RawEntry {
+                    line: 5,
+                    key: "token",
+                    value: "second"
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_parse_bool() {
         assert_eq!(parse_bool("true"), Some(true));
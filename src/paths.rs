@@ -3,7 +3,9 @@
 //! This module handles discovering the locations of various .npmrc files
 //! following npm's resolution logic.
 
-use std::path::{Path, PathBuf};
+use crate::fs::{FileSystem, RealFileSystem};
+use glob::{MatchOptions, Pattern};
+use std::path::{Component, Path, PathBuf};
 
 /// Find the global prefix by locating the node executable and deriving
 /// the prefix from its location.
@@ -31,42 +33,153 @@ pub fn find_global_prefix() -> Option<PathBuf> {
     }
 }
 
+/// Result of local prefix discovery.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalPrefix {
+    /// The local prefix. This is the workspace root when the starting
+    /// directory is inside a workspace.
+    pub prefix: PathBuf,
+    /// The workspace the starting directory belongs to, if `prefix` is a
+    /// workspace root.
+    pub workspace: Option<PathBuf>,
+}
+
 /// Walk up from the given directory looking for the first directory containing
 /// either a `package.json` file or a `node_modules` directory.
 ///
+/// If that directory is a workspace of a parent package (its path matches one
+/// of the parent's `workspaces` globs), the workspace root is returned instead.
+///
 /// Falls back to the starting directory if nothing is found.
 pub fn find_local_prefix(cwd: &Path) -> PathBuf {
-    let mut current = cwd.to_path_buf();
+    find_local_prefix_in(&RealFileSystem, cwd)
+}
 
-    loop {
-        // Check for package.json
-        if current.join("package.json").is_file() {
-            return current;
-        }
+/// Like [`find_local_prefix`], but checks for files through `fs`.
+pub fn find_local_prefix_in(fs: &dyn FileSystem, cwd: &Path) -> PathBuf {
+    discover_local_prefix(fs, cwd).prefix
+}
 
-        // Check for node_modules directory
-        if current.join("node_modules").is_dir() {
-            return current;
-        }
+/// Discover the local prefix and, when inside a workspace, the current workspace.
+///
+/// Mirrors npm: after finding the nearest directory with a `package.json` or
+/// `node_modules`, keep walking up looking for a `package.json` whose
+/// `workspaces` (array or `{ "packages": [...] }` form) include it.
+pub fn discover_local_prefix(fs: &dyn FileSystem, cwd: &Path) -> LocalPrefix {
+    let mut ancestors = cwd.ancestors();
+
+    // Find the nearest package directory
+    let Some(nearest) = ancestors
+        .by_ref()
+        .find(|dir| fs.is_file(&dir.join("package.json")) || fs.is_dir(&dir.join("node_modules")))
+    else {
+        // Fall back to cwd if nothing found
+        return LocalPrefix {
+            prefix: cwd.to_path_buf(),
+            workspace: None,
+        };
+    };
 
-        // Move to parent directory
-        match current.parent() {
-            Some(parent) if parent != current => {
-                current = parent.to_path_buf();
+    // Look for a workspace root that includes it
+    if fs.is_file(&nearest.join("package.json")) {
+        for dir in ancestors {
+            let patterns = read_workspace_patterns(fs, &dir.join("package.json"));
+            if workspace_matches(dir, &patterns, nearest) {
+                return LocalPrefix {
+                    prefix: dir.to_path_buf(),
+                    workspace: Some(nearest.to_path_buf()),
+                };
             }
-            _ => break,
         }
     }
 
-    // Fall back to cwd if nothing found
-    cwd.to_path_buf()
+    LocalPrefix {
+        prefix: nearest.to_path_buf(),
+        workspace: None,
+    }
+}
+
+/// Read the `workspaces` globs from a `package.json`.
+///
+/// Returns an empty list if the file is missing, invalid, or has no workspaces.
+fn read_workspace_patterns(fs: &dyn FileSystem, path: &Path) -> Vec<String> {
+    let Ok(content) = fs.read_to_string(path) else {
+        return Vec::new();
+    };
+    let Ok(pkg) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return Vec::new();
+    };
+
+    let workspaces = match pkg.get("workspaces") {
+        Some(serde_json::Value::Object(obj)) => obj.get("packages"),
+        other => other,
+    };
+
+    workspaces
+        .and_then(|w| w.as_array())
+        .map(|patterns| {
+            patterns
+                .iter()
+                .filter_map(|p| p.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Check whether `dir` is matched by the workspace globs of `root`.
+///
+/// Patterns are applied in order, so a later `!pattern` excludes directories
+/// matched earlier. Directories inside `node_modules` never match.
+fn workspace_matches(root: &Path, patterns: &[String], dir: &Path) -> bool {
+    let Ok(relative) = dir.strip_prefix(root) else {
+        return false;
+    };
+
+    let mut parts = Vec::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) if part != "node_modules" => parts.push(part.to_string_lossy()),
+            _ => return false,
+        }
+    }
+    if parts.is_empty() {
+        return false;
+    }
+    let relative = parts.join("/");
+
+    let options = MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+
+    let mut included = false;
+    for pattern in patterns {
+        let (negated, pattern) = match pattern.trim().strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, pattern.trim()),
+        };
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        let Ok(glob) = Pattern::new(pattern) else {
+            continue;
+        };
+        if glob.matches_with(&relative, options) {
+            included = !negated;
+        }
+    }
+    included
 }
 
 /// Get the path to the user's .npmrc file (`~/.npmrc`).
 ///
 /// Returns `None` if the home directory cannot be determined.
 pub fn user_config_path() -> Option<PathBuf> {
-    dirs::home_dir().map(|home| home.join(".npmrc"))
+    dirs::home_dir().map(|home| user_config_path_in(&home))
+}
+
+/// Get the path to the user's .npmrc file under the given home directory.
+pub fn user_config_path_in(home: &Path) -> PathBuf {
+    home.join(".npmrc")
 }
 
 /// Get the path to the global .npmrc file (`{globalPrefix}/etc/npmrc`).
@@ -81,13 +194,20 @@ pub fn project_config_path(prefix: &Path) -> PathBuf {
 
 /// Expand `~` at the start of a path to the user's home directory.
 pub fn expand_tilde(path: &str) -> PathBuf {
+    expand_tilde_with(path, dirs::home_dir().as_deref())
+}
+
+/// Expand `~` at the start of a path to the given home directory.
+///
+/// The path is returned unchanged when `home` is `None`.
+pub fn expand_tilde_with(path: &str, home: Option<&Path>) -> PathBuf {
     if let Some(rest) = path.strip_prefix("~/") {
-        if let Some(home) = dirs::home_dir() {
+        if let Some(home) = home {
             return home.join(rest);
         }
     } else if path == "~" {
-        if let Some(home) = dirs::home_dir() {
-            return home;
+        if let Some(home) = home {
+            return home.to_path_buf();
         }
     }
     PathBuf::from(path)
@@ -150,6 +270,101 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_local_prefix_in_memory() {
+        use crate::fs::MemoryFileSystem;
+
+        let fs = MemoryFileSystem::new()
+            .with_file("/work/project/package.json", "{}")
+            .with_dir("/work/project/src/lib");
+
+        assert_eq!(
+            find_local_prefix_in(&fs, Path::new("/work/project/src/lib")),
+            PathBuf::from("/work/project")
+        );
+        assert_eq!(
+            find_local_prefix_in(&fs, Path::new("/work")),
+            PathBuf::from("/work")
+        );
+    }
+
+    #[test]
+    fn test_discover_workspace_array() {
+        use crate::fs::MemoryFileSystem;
+
+        let fs = MemoryFileSystem::new()
+            .with_file("/repo/package.json", r#"{"workspaces": ["packages/*"]}"#)
+            .with_file("/repo/packages/foo/package.json", "{}")
+            .with_dir("/repo/packages/foo/src");
+
+        let found = discover_local_prefix(&fs, Path::new("/repo/packages/foo/src"));
+        assert_eq!(found.prefix, PathBuf::from("/repo"));
+        assert_eq!(found.workspace, Some(PathBuf::from("/repo/packages/foo")));
+    }
+
+    #[test]
+    fn test_discover_workspace_object_form() {
+        use crate::fs::MemoryFileSystem;
+
+        let fs = MemoryFileSystem::new()
+            .with_file(
+                "/repo/package.json",
+                r#"{"workspaces": {"packages": ["./apps/**"]}}"#,
+            )
+            .with_file("/repo/apps/web/ui/package.json", "{}");
+
+        let found = discover_local_prefix(&fs, Path::new("/repo/apps/web/ui"));
+        assert_eq!(found.prefix, PathBuf::from("/repo"));
+        assert_eq!(found.workspace, Some(PathBuf::from("/repo/apps/web/ui")));
+    }
+
+    #[test]
+    fn test_discover_workspace_not_matched() {
+        use crate::fs::MemoryFileSystem;
+
+        let fs = MemoryFileSystem::new()
+            .with_file(
+                "/repo/package.json",
+                r#"{"workspaces": ["packages/*", "!packages/private"]}"#,
+            )
+            .with_file("/repo/packages/private/package.json", "{}")
+            .with_file("/repo/tools/script/package.json", "{}");
+
+        let found = discover_local_prefix(&fs, Path::new("/repo/packages/private"));
+        assert_eq!(found.prefix, PathBuf::from("/repo/packages/private"));
+        assert_eq!(found.workspace, None);
+
+        let found = discover_local_prefix(&fs, Path::new("/repo/tools/script"));
+        assert_eq!(found.prefix, PathBuf::from("/repo/tools/script"));
+        assert_eq!(found.workspace, None);
+    }
+
+    #[test]
+    fn test_discover_workspace_requires_package_json() {
+        use crate::fs::MemoryFileSystem;
+
+        // A directory found only through node_modules is not a workspace
+        let fs = MemoryFileSystem::new()
+            .with_file("/repo/package.json", r#"{"workspaces": ["packages/*"]}"#)
+            .with_dir("/repo/packages/foo/node_modules");
+
+        let found = discover_local_prefix(&fs, Path::new("/repo/packages/foo"));
+        assert_eq!(found.prefix, PathBuf::from("/repo/packages/foo"));
+        assert_eq!(found.workspace, None);
+    }
+
+    #[test]
+    fn test_expand_tilde_with_home() {
+        let home = Path::new("/custom/home");
+
+        assert_eq!(
+            expand_tilde_with("~/certs/a.pem", Some(home)),
+            PathBuf::from("/custom/home/certs/a.pem")
+        );
+        assert_eq!(expand_tilde_with("~", Some(home)), PathBuf::from(home));
+        assert_eq!(expand_tilde_with("~/x", None), PathBuf::from("~/x"));
+    }
+
     #[test]
     fn test_global_config_path() {
         let prefix = PathBuf::from("/usr/local");
@@ -0,0 +1,266 @@
+//! Permission audit for config files that hold credentials.
+//!
+//! On Unix, a `.npmrc` containing a token should be readable only by its
+//! owner. Other platforms have no comparable mode bits, so the audit reports
+//! nothing there.
+
+use crate::auth::is_credential_key;
+use crate::config::NpmrcConfig;
+use crate::error::{Error, Result};
+use crate::fs::FileSystem;
+use std::path::PathBuf;
+
+/// Mode that [`PermissionIssue::fix`] applies: read/write for the owner only.
+pub const SECURE_MODE: u32 = 0o600;
+
+/// A specific problem with a config file's permissions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionProblem {
+    /// Members of the file's group can read or write it.
+    GroupAccessible,
+    /// Any user can read or write it.
+    WorldAccessible,
+    /// The file is owned by a different user than the current process.
+    NotOwnedByCurrentUser {
+        /// Owner uid of the file.
+        owner: u32,
+    },
+}
+
+/// A credential-bearing config file with insecure permissions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionIssue {
+    /// Name of the layer the file was loaded into.
+    pub layer: String,
+    /// Path to the file.
+    pub path: PathBuf,
+    /// Permission bits of the file (`mode & 0o777`).
+    pub mode: u32,
+    /// Problems found, never empty.
+    pub problems: Vec<PermissionProblem>,
+}
+
+impl PermissionIssue {
+    /// Tighten the file's mode to [`SECURE_MODE`] (`0600`).
+    ///
+    /// Pass the filesystem the audit used, [`NpmrcConfig::fs`], so the change
+    /// is made to the same file. Ownership problems are not changed.
+    pub fn fix(&self, fs: &dyn FileSystem) -> Result<()> {
+        fs.set_permissions(&self.path, SECURE_MODE)
+            .map_err(|e| Error::SetPermissions {
+                path: self.path.clone(),
+                source: e,
+            })
+    }
+}
+
+/// Get the uid of the current process.
+#[cfg(unix)]
+fn current_uid() -> Option<u32> {
+    // SAFETY: getuid has no preconditions and cannot fail.
+    Some(unsafe { libc::getuid() })
+}
+
+#[cfg(not(unix))]
+fn current_uid() -> Option<u32> {
+    None
+}
+
+/// Inspect the permissions of a config file through the config's filesystem.
+fn inspect(fs: &dyn FileSystem, layer: &str, path: PathBuf) -> Result<Option<PermissionIssue>> {
+    let permissions = match fs.permissions(&path) {
+        Ok(Some(permissions)) => permissions,
+        Ok(None) => return Ok(None),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(Error::ReadFile { path, source: e }),
+    };
+
+    let mode = permissions.mode & 0o777;
+    let mut problems = Vec::new();
+    if mode & 0o070 != 0 {
+        problems.push(PermissionProblem::GroupAccessible);
+    }
+    if mode & 0o007 != 0 {
+        problems.push(PermissionProblem::WorldAccessible);
+    }
+    if current_uid().is_some_and(|uid| permissions.uid != uid) {
+        problems.push(PermissionProblem::NotOwnedByCurrentUser {
+            owner: permissions.uid,
+        });
+    }
+
+    Ok((!problems.is_empty()).then(|| PermissionIssue {
+        layer: layer.to_string(),
+        path,
+        mode,
+        problems,
+    }))
+}
+
+impl NpmrcConfig {
+    /// Check the permissions of every loaded config file that holds credentials.
+    ///
+    /// Reports files that are group or world accessible, or owned by another
+    /// user. Permissions are read through [`NpmrcConfig::fs`], like the
+    /// config itself, so a [`MemoryFileSystem`](crate::MemoryFileSystem)
+    /// only reports files it was given permissions for. In-memory layers and
+    /// missing sources are skipped. With the real filesystem, always returns
+    /// an empty list on non-Unix platforms.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use npmrc_config_rs::NpmrcConfig;
+    ///
+    /// let config = NpmrcConfig::load()?;
+    /// for issue in config.audit_permissions()? {
+    ///     eprintln!("{} has mode {:o}", issue.path.display(), issue.mode);
+    ///     issue.fix(config.fs())?;
+    /// }
+    /// # Ok::<(), npmrc_config_rs::Error>(())
+    /// ```
+    pub fn audit_permissions(&self) -> Result<Vec<PermissionIssue>> {
+        let mut issues = Vec::new();
+        for layer in self.layers() {
+            if layer.in_memory || !layer.data.data.keys().any(|k| is_credential_key(k)) {
+                continue;
+            }
+            if let Some(issue) = inspect(self.fs(), &layer.name, layer.data.source.clone())? {
+                issues.push(issue);
+            }
+        }
+        Ok(issues)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::config::LoadOptions;
+    use crate::fs::{FilePermissions, MemoryFileSystem};
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn load(dir: &Path) -> NpmrcConfig {
+        NpmrcConfig::load_with_options(LoadOptions {
+            cwd: Some(dir.to_path_buf()),
+            user_config: Some(dir.join("user.npmrc")),
+            skip_global: true,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn set_mode(path: &Path, mode: u32) {
+        fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[test]
+    fn test_audit_and_fix_permissions() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        fs::write(dir.join("package.json"), "{}").unwrap();
+        fs::write(dir.join(".npmrc"), "registry = https://example.com/\n").unwrap();
+        fs::write(dir.join("user.npmrc"), "//example.com/:_authToken = t\n").unwrap();
+        set_mode(&dir.join(".npmrc"), 0o644);
+        set_mode(&dir.join("user.npmrc"), 0o640);
+
+        let config = load(dir);
+        let issues = config.audit_permissions().unwrap();
+        assert_eq!(issues.len(), 1, "only the credential file is audited");
+        assert_eq!(issues[0].layer, "user");
+        assert_eq!(issues[0].mode, 0o640);
+        assert_eq!(issues[0].problems, vec![PermissionProblem::GroupAccessible]);
+
+        issues[0].fix(config.fs()).unwrap();
+        let mode = fs::metadata(dir.join("user.npmrc"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, SECURE_MODE);
+        assert!(config.audit_permissions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_audit_reports_world_accessible() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        fs::write(dir.join("package.json"), "{}").unwrap();
+        fs::write(dir.join(".npmrc"), "_auth = dXNlcjpwYXNz\n").unwrap();
+        set_mode(&dir.join(".npmrc"), 0o604);
+
+        let issues = load(dir).audit_permissions().unwrap();
+        assert_eq!(issues[0].problems, vec![PermissionProblem::WorldAccessible]);
+    }
+
+    #[test]
+    fn test_audit_uses_config_filesystem() {
+        let uid = current_uid().unwrap();
+        let fs = MemoryFileSystem::new()
+            .with_file("/project/package.json", "{}")
+            .with_file("/project/.npmrc", "//a.com/:_authToken = t\n")
+            .with_file("/home/.npmrc", "//b.com/:_authToken = t\n")
+            .with_permissions(
+                "/project/.npmrc",
+                FilePermissions {
+                    mode: 0o644,
+                    uid: uid + 1,
+                },
+            );
+        let config = NpmrcConfig::load_with_options(LoadOptions {
+            cwd: Some("/project".into()),
+            user_config: Some("/home/.npmrc".into()),
+            fs: Some(Arc::new(fs)),
+            skip_global: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        // The user file has no permissions in memory and is not looked up on disk
+        let issues = config.audit_permissions().unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, PathBuf::from("/project/.npmrc"));
+        assert_eq!(
+            issues[0].problems,
+            vec![
+                PermissionProblem::GroupAccessible,
+                PermissionProblem::WorldAccessible,
+                PermissionProblem::NotOwnedByCurrentUser { owner: uid + 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fix_uses_config_filesystem() {
+        let temp = TempDir::new().unwrap();
+        let npmrc = temp.path().join(".npmrc");
+        fs::write(&npmrc, "").unwrap();
+        set_mode(&npmrc, 0o644);
+
+        let uid = current_uid().unwrap();
+        let memory = MemoryFileSystem::new()
+            .with_file(temp.path().join("package.json"), "{}")
+            .with_file(&npmrc, "//a.com/:_authToken = t\n")
+            .with_permissions(&npmrc, FilePermissions { mode: 0o644, uid });
+        let config = NpmrcConfig::load_with_options(LoadOptions {
+            cwd: Some(temp.path().to_path_buf()),
+            fs: Some(Arc::new(memory)),
+            skip_user: true,
+            skip_global: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let issues = config.audit_permissions().unwrap();
+        assert_eq!(issues.len(), 1);
+        issues[0].fix(config.fs()).unwrap();
+        assert!(config.audit_permissions().unwrap().is_empty());
+
+        // The file on disk at the same path is left alone
+        let mode = fs::metadata(&npmrc).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o644);
+    }
+}
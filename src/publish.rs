@@ -0,0 +1,254 @@
+//! Publish-time registry resolution.
+//!
+//! `npm publish` reads `publishConfig` from the package's `package.json` and
+//! applies it on top of every `.npmrc`, so a package can pin its own
+//! registry, dist-tag or access level.
+
+use crate::auth::{is_credential_key, Credentials};
+use crate::config::{ConfigLayer, NpmrcConfig};
+use crate::error::{Error, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use url::Url;
+
+/// Name of the layer holding a manifest's `publishConfig`.
+const PUBLISH_CONFIG_LAYER: &str = "publishConfig";
+
+/// Where and how a package would be published.
+#[derive(Debug, Clone)]
+pub struct PublishTarget {
+    /// Package name from the manifest.
+    pub name: String,
+    /// Registry the package is published to.
+    pub registry: Url,
+    /// Credentials for the registry, if any.
+    pub credentials: Option<Credentials>,
+    /// The config with `publishConfig` applied as its top layer.
+    pub config: NpmrcConfig,
+}
+
+impl PublishTarget {
+    /// Get the dist-tag to publish under (`tag`, default: `latest`).
+    pub fn tag(&self) -> &str {
+        self.config.get("tag").unwrap_or("latest")
+    }
+
+    /// Get the access level (`access`), e.g. `public` or `restricted`.
+    pub fn access(&self) -> Option<&str> {
+        self.config.get("access")
+    }
+}
+
+impl NpmrcConfig {
+    /// Resolve the registry and credentials `npm publish` would use for a package.
+    ///
+    /// Reads the manifest through [`NpmrcConfig::fs`] and applies its
+    /// `publishConfig` as a temporary top layer, so `publishConfig.registry`
+    /// and `publishConfig["@scope:registry"]` override every `.npmrc`. Other
+    /// keys such as `tag` and `access` are available through
+    /// [`PublishTarget::config`]. String, boolean and number values are
+    /// applied; other values are ignored. Nerf-darted keys (`//host/:...`,
+    /// including `tokenHelper`) and credential fields are dropped, so a
+    /// manifest can't run a command or supply the credentials that get used.
+    /// `self` is not modified.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ReadFile`] if the manifest can't be read,
+    /// [`Error::ParseManifest`] if it is not a JSON object with a `name`, and
+    /// [`Error::TokenHelper`] if the registry's token helper fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use npmrc_config_rs::NpmrcConfig;
+    ///
+    /// let config = NpmrcConfig::load()?;
+    /// let target = config.publish_registry_for("package.json")?;
+    /// println!("publishing {} to {} as {}", target.name, target.registry, target.tag());
+    /// # Ok::<(), npmrc_config_rs::Error>(())
+    /// ```
+    pub fn publish_registry_for(&self, package_json: impl AsRef<Path>) -> Result<PublishTarget> {
+        let path = package_json.as_ref();
+        let content = self
+            .fs()
+            .read_to_string(path)
+            .map_err(|e| Error::ReadFile {
+                path: path.to_path_buf(),
+                source: e,
+            })?;
+        let invalid = |message: &str| Error::ParseManifest {
+            path: path.to_path_buf(),
+            message: message.to_string(),
+        };
+
+        let manifest: serde_json::Value =
+            serde_json::from_str(&content).map_err(|e| invalid(&e.to_string()))?;
+        let manifest = manifest
+            .as_object()
+            .ok_or_else(|| invalid("expected a JSON object"))?;
+        let name = manifest
+            .get("name")
+            .and_then(|name| name.as_str())
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| invalid("missing \"name\""))?;
+
+        let publish_config: HashMap<String, String> = manifest
+            .get("publishConfig")
+            .and_then(|c| c.as_object())
+            .into_iter()
+            .flatten()
+            .filter(|(key, _)| !key.starts_with("//") && !is_credential_key(key))
+            .filter_map(|(key, value)| {
+                let value = match value {
+                    serde_json::Value::String(s) => s.clone(),
+                    serde_json::Value::Bool(b) => b.to_string(),
+                    serde_json::Value::Number(n) => n.to_string(),
+                    _ => return None,
+                };
+                Some((key.clone(), value))
+            })
+            .collect();
+
+        let mut config = self.clone();
+        config.push_layer(ConfigLayer::from_map(
+            PUBLISH_CONFIG_LAYER,
+            i32::MAX,
+            publish_config,
+        ));
+
+        let registry = config.registry_for(name);
+        let credentials = config.try_credentials_for(&registry)?;
+        Ok(PublishTarget {
+            name: name.to_string(),
+            registry,
+            credentials,
+            config,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LoadOptions;
+    use crate::fs::MemoryFileSystem;
+    use std::sync::Arc;
+
+    fn load(package_json: &str) -> NpmrcConfig {
+        let fs = MemoryFileSystem::new()
+            .with_file("/project/package.json", package_json)
+            .with_file(
+                "/project/.npmrc",
+                "@corp:registry = https://npm.corp.com/\n\
+                 //npm.corp.com/:_authToken = corp-token\n\
+                 //publish.corp.com/:_authToken = publish-token\n\
+                 tag = next\n",
+            );
+        NpmrcConfig::load_with_options(LoadOptions {
+            cwd: Some("/project".into()),
+            fs: Some(Arc::new(fs)),
+            skip_user: true,
+            skip_global: true,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_publish_registry_without_publish_config() {
+        let config = load(r#"{"name": "@corp/pkg"}"#);
+        let target = config
+            .publish_registry_for("/project/package.json")
+            .unwrap();
+        assert_eq!(target.registry.as_str(), "https://npm.corp.com/");
+        assert_eq!(
+            target.credentials.as_ref().unwrap().token(),
+            Some("corp-token")
+        );
+        assert_eq!(target.tag(), "next");
+        assert_eq!(target.access(), None);
+    }
+
+    #[test]
+    fn test_publish_config_overrides_npmrc() {
+        let config = load(
+            r#"{
+                "name": "@corp/pkg",
+                "publishConfig": {
+                    "@corp:registry": "https://publish.corp.com/",
+                    "tag": "beta",
+                    "access": "restricted",
+                    "provenance": true
+                }
+            }"#,
+        );
+        let target = config
+            .publish_registry_for("/project/package.json")
+            .unwrap();
+        assert_eq!(target.name, "@corp/pkg");
+        assert_eq!(target.registry.as_str(), "https://publish.corp.com/");
+        assert_eq!(
+            target.credentials.as_ref().unwrap().token(),
+            Some("publish-token")
+        );
+        assert_eq!(target.tag(), "beta");
+        assert_eq!(target.access(), Some("restricted"));
+        assert_eq!(target.config.get("provenance"), Some("true"));
+
+        // The original config is untouched
+        assert_eq!(config.get("tag"), Some("next"));
+        assert!(config.layer(PUBLISH_CONFIG_LAYER).is_none());
+    }
+
+    #[test]
+    fn test_publish_config_ignores_auth_keys() {
+        let config = load(
+            r#"{
+                "name": "@corp/pkg",
+                "publishConfig": {
+                    "@corp:registry": "https://publish.corp.com/",
+                    "//publish.corp.com/:tokenHelper": "/bin/sh",
+                    "//publish.corp.com/:_authToken": "manifest-token",
+                    "_authToken": "manifest-token",
+                    "username": "manifest-user"
+                }
+            }"#,
+        );
+        let target = config
+            .publish_registry_for("/project/package.json")
+            .unwrap();
+        assert_eq!(target.registry.as_str(), "https://publish.corp.com/");
+        assert_eq!(
+            target.credentials.as_ref().unwrap().token(),
+            Some("publish-token")
+        );
+        let layer = target.config.layer(PUBLISH_CONFIG_LAYER).unwrap();
+        assert_eq!(layer.data.data.len(), 1);
+        assert!(layer.data.get("@corp:registry").is_some());
+    }
+
+    #[test]
+    fn test_publish_config_registry_for_unscoped_package() {
+        let config =
+            load(r#"{"name": "pkg", "publishConfig": {"registry": "https://publish.corp.com"}}"#);
+        let target = config
+            .publish_registry_for("/project/package.json")
+            .unwrap();
+        assert_eq!(target.registry.as_str(), "https://publish.corp.com/");
+        assert!(target.credentials.is_some());
+    }
+
+    #[test]
+    fn test_publish_registry_invalid_manifest() {
+        let config = load(r#"{"version": "1.0.0"}"#);
+        assert!(matches!(
+            config.publish_registry_for("/project/package.json"),
+            Err(Error::ParseManifest { .. })
+        ));
+        assert!(matches!(
+            config.publish_registry_for("/missing/package.json"),
+            Err(Error::ReadFile { .. })
+        ));
+    }
+}
@@ -3,11 +3,118 @@
 //! This module handles resolving the correct registry URL for packages,
 //! including support for scoped registries.
 
+use std::fmt;
 use url::Url;
 
 /// The default npm registry URL.
 pub const DEFAULT_REGISTRY: &str = "https://registry.npmjs.org/";
 
+/// `Accept` header for abbreviated ("corgi") package metadata, as sent by npm
+/// for installs.
+pub const ABBREVIATED_METADATA_ACCEPT: &str =
+    "application/vnd.npm.install-v1+json; q=1.0, application/json; q=0.8, */*";
+
+/// `Accept` header for full package metadata.
+pub const FULL_METADATA_ACCEPT: &str = "application/json";
+
+/// Host of the public npm registry.
+const NPMJS_HOST: &str = "registry.npmjs.org";
+
+/// npm's `replace-registry-host` setting.
+///
+/// Controls whether `resolved` tarball URLs from lockfiles are rewritten to
+/// point at the configured registry.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ReplaceRegistryHost {
+    /// Rewrite URLs on `registry.npmjs.org` (npm's default).
+    #[default]
+    Npmjs,
+    /// Never rewrite URLs.
+    Never,
+    /// Rewrite URLs on any host.
+    Always,
+    /// Rewrite URLs on the given hostname.
+    Host(String),
+}
+
+impl ReplaceRegistryHost {
+    /// Check whether a resolved URL on `host` should be rewritten.
+    pub fn matches(&self, host: &str) -> bool {
+        match self {
+            ReplaceRegistryHost::Npmjs => host == NPMJS_HOST,
+            ReplaceRegistryHost::Never => false,
+            ReplaceRegistryHost::Always => true,
+            ReplaceRegistryHost::Host(h) => host == h,
+        }
+    }
+}
+
+impl From<&str> for ReplaceRegistryHost {
+    fn from(value: &str) -> Self {
+        match value {
+            "" | "npmjs" => ReplaceRegistryHost::Npmjs,
+            "never" => ReplaceRegistryHost::Never,
+            "always" => ReplaceRegistryHost::Always,
+            host => ReplaceRegistryHost::Host(host.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ReplaceRegistryHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplaceRegistryHost::Npmjs => f.write_str("npmjs"),
+            ReplaceRegistryHost::Never => f.write_str("never"),
+            ReplaceRegistryHost::Always => f.write_str("always"),
+            ReplaceRegistryHost::Host(h) => f.write_str(h),
+        }
+    }
+}
+
+/// Rewrite a lockfile `resolved` URL to the registry npm would fetch it from.
+///
+/// When `mode` matches the URL's hostname (ignoring the port), the whole
+/// path and query are appended to `registry`, as npm's Arborist does.
+/// Otherwise the URL is returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use npmrc_config_rs::registry::{rewrite_resolved_url, ReplaceRegistryHost};
+/// use url::Url;
+///
+/// let resolved = Url::parse("https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz").unwrap();
+/// let mirror = Url::parse("https://mirror.corp.com/npm/").unwrap();
+///
+/// assert_eq!(
+///     rewrite_resolved_url(&resolved, &mirror, &ReplaceRegistryHost::Npmjs).as_str(),
+///     "https://mirror.corp.com/npm/lodash/-/lodash-4.17.21.tgz"
+/// );
+/// assert_eq!(
+///     rewrite_resolved_url(&resolved, &mirror, &ReplaceRegistryHost::Never),
+///     resolved
+/// );
+/// ```
+pub fn rewrite_resolved_url(resolved: &Url, registry: &Url, mode: &ReplaceRegistryHost) -> Url {
+    let Some(host) = resolved.host_str() else {
+        return resolved.clone();
+    };
+    if !mode.matches(host) {
+        return resolved.clone();
+    }
+
+    let mut rewritten = format!(
+        "{}{}",
+        registry.as_str().trim_end_matches('/'),
+        resolved.path()
+    );
+    if let Some(query) = resolved.query() {
+        rewritten.push('?');
+        rewritten.push_str(query);
+    }
+    Url::parse(&rewritten).unwrap_or_else(|_| resolved.clone())
+}
+
 /// Extract the scope from a package name if present.
 ///
 /// # Examples
@@ -42,6 +149,79 @@ pub fn scope_registry_key(scope: &str) -> String {
     format!("{}:registry", scope)
 }
 
+/// Escape a package name for use as a single URL path segment.
+///
+/// Scoped names have their `/` encoded, as the registry expects.
+///
+/// # Examples
+///
+/// ```
+/// use npmrc_config_rs::registry::escape_package_name;
+///
+/// assert_eq!(escape_package_name("@myorg/package"), "@myorg%2fpackage");
+/// assert_eq!(escape_package_name("lodash"), "lodash");
+/// ```
+pub fn escape_package_name(name: &str) -> String {
+    if name.starts_with('@') {
+        name.replacen('/', "%2f", 1)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Build the packument (package metadata) URL for a package.
+///
+/// Registries that live under a path keep their path prefix.
+///
+/// # Examples
+///
+/// ```
+/// use npmrc_config_rs::registry::packument_url;
+/// use url::Url;
+///
+/// let registry = Url::parse("https://npm.example.com/api/npm/").unwrap();
+/// assert_eq!(
+///     packument_url(&registry, "@myorg/package").unwrap().as_str(),
+///     "https://npm.example.com/api/npm/@myorg%2fpackage"
+/// );
+/// ```
+pub fn packument_url(registry: &Url, name: &str) -> Result<Url, url::ParseError> {
+    Url::parse(&format!(
+        "{}/{}",
+        registry.as_str().trim_end_matches('/'),
+        escape_package_name(name)
+    ))
+}
+
+/// Build the conventional tarball URL for a package version.
+///
+/// This is `{registry}/{name}/-/{basename}-{version}.tgz`, the layout used by
+/// the public registry and most registry implementations. The actual URL is
+/// given by `dist.tarball` in the packument and may differ.
+///
+/// # Examples
+///
+/// ```
+/// use npmrc_config_rs::registry::tarball_url;
+/// use url::Url;
+///
+/// let registry = Url::parse("https://registry.npmjs.org/").unwrap();
+/// assert_eq!(
+///     tarball_url(&registry, "@myorg/package", "1.0.0").unwrap().as_str(),
+///     "https://registry.npmjs.org/@myorg/package/-/package-1.0.0.tgz"
+/// );
+/// ```
+pub fn tarball_url(registry: &Url, name: &str, version: &str) -> Result<Url, url::ParseError> {
+    let basename = name.rsplit('/').next().unwrap_or(name);
+    Url::parse(&format!(
+        "{}/{}/-/{}-{}.tgz",
+        registry.as_str().trim_end_matches('/'),
+        name,
+        basename,
+        version
+    ))
+}
+
 /// Parse a registry URL, ensuring it has a trailing slash.
 pub fn parse_registry_url(url: &str) -> Result<Url, url::ParseError> {
     let normalized = if url.ends_with('/') {
@@ -70,6 +250,102 @@ mod tests {
         assert_eq!(scope_registry_key("@acme"), "@acme:registry");
     }
 
+    #[test]
+    fn test_escape_package_name() {
+        assert_eq!(escape_package_name("@scope/name"), "@scope%2fname");
+        assert_eq!(escape_package_name("name"), "name");
+    }
+
+    #[test]
+    fn test_packument_url() {
+        let registry = Url::parse("https://registry.npmjs.org/").unwrap();
+        assert_eq!(
+            packument_url(&registry, "lodash").unwrap().as_str(),
+            "https://registry.npmjs.org/lodash"
+        );
+        assert_eq!(
+            packument_url(&registry, "@scope/name").unwrap().as_str(),
+            "https://registry.npmjs.org/@scope%2fname"
+        );
+
+        // Registry without trailing slash keeps its path
+        let registry = Url::parse("https://example.com/npm").unwrap();
+        assert_eq!(
+            packument_url(&registry, "@scope/name").unwrap().as_str(),
+            "https://example.com/npm/@scope%2fname"
+        );
+    }
+
+    #[test]
+    fn test_tarball_url() {
+        let registry = Url::parse("https://example.com/repository/npm/").unwrap();
+        assert_eq!(
+            tarball_url(&registry, "lodash", "4.17.21")
+                .unwrap()
+                .as_str(),
+            "https://example.com/repository/npm/lodash/-/lodash-4.17.21.tgz"
+        );
+        assert_eq!(
+            tarball_url(&registry, "@scope/name", "1.0.0-beta.1")
+                .unwrap()
+                .as_str(),
+            "https://example.com/repository/npm/@scope/name/-/name-1.0.0-beta.1.tgz"
+        );
+    }
+
+    #[test]
+    fn test_replace_registry_host_from_str() {
+        assert_eq!(
+            ReplaceRegistryHost::from("npmjs"),
+            ReplaceRegistryHost::Npmjs
+        );
+        assert_eq!(
+            ReplaceRegistryHost::from("never"),
+            ReplaceRegistryHost::Never
+        );
+        assert_eq!(
+            ReplaceRegistryHost::from("always"),
+            ReplaceRegistryHost::Always
+        );
+        assert_eq!(
+            ReplaceRegistryHost::from("npm.corp.com"),
+            ReplaceRegistryHost::Host("npm.corp.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rewrite_resolved_url() {
+        let registry = Url::parse("https://mirror.corp.com/api/npm/").unwrap();
+        let npmjs = Url::parse("https://registry.npmjs.org/@s/p/-/p-1.0.0.tgz").unwrap();
+        let other = Url::parse("https://npm.other.com:8443/x/-/x-1.0.0.tgz?sig=1").unwrap();
+
+        assert_eq!(
+            rewrite_resolved_url(&npmjs, &registry, &ReplaceRegistryHost::Npmjs).as_str(),
+            "https://mirror.corp.com/api/npm/@s/p/-/p-1.0.0.tgz"
+        );
+        assert_eq!(
+            rewrite_resolved_url(&other, &registry, &ReplaceRegistryHost::Npmjs),
+            other
+        );
+        assert_eq!(
+            rewrite_resolved_url(&other, &registry, &ReplaceRegistryHost::Always).as_str(),
+            "https://mirror.corp.com/api/npm/x/-/x-1.0.0.tgz?sig=1"
+        );
+        assert_eq!(
+            rewrite_resolved_url(
+                &other,
+                &registry,
+                &ReplaceRegistryHost::Host("npm.other.com".to_string())
+            )
+            .as_str(),
+            "https://mirror.corp.com/api/npm/x/-/x-1.0.0.tgz?sig=1"
+        );
+        assert_eq!(
+            rewrite_resolved_url(&npmjs, &registry, &ReplaceRegistryHost::Never),
+            npmjs
+        );
+    }
+
     #[test]
     fn test_parse_registry_url() {
         let url = parse_registry_url("https://registry.npmjs.org").unwrap();
@@ -0,0 +1,97 @@
+//! Wrapper for secret strings such as tokens and passwords.
+//!
+//! With the `zeroize` feature enabled, secret memory is overwritten with
+//! zeros when it is dropped. The API is the same with or without the feature.
+
+use std::fmt;
+
+/// A secret string, such as a token or a decoded password.
+///
+/// - `Debug` prints `[REDACTED]`.
+/// - `Display` is not implemented, so the value can't end up in a
+///   `format!` by accident.
+/// - The value is only reachable through [`SecretString::expose_secret`].
+/// - With the `zeroize` feature, the memory is wiped on drop.
+///
+/// # Examples
+///
+/// ```
+/// use npmrc_config_rs::SecretString;
+///
+/// let token = SecretString::from("npm_secret");
+/// assert_eq!(token.expose_secret(), "npm_secret");
+/// assert_eq!(format!("{:?}", token), "[REDACTED]");
+/// ```
+#[derive(Clone, Default)]
+pub struct SecretString(String);
+
+impl SecretString {
+    /// Wrap a secret value.
+    pub fn new(secret: impl Into<String>) -> Self {
+        SecretString(secret.into())
+    }
+
+    /// Get the secret value.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+
+    /// Check whether the secret is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        SecretString(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> Self {
+        SecretString(secret.to_string())
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+/// Overwrite a string's memory with zeros if the `zeroize` feature is enabled.
+///
+/// Clears the string either way.
+pub(crate) fn wipe(value: &mut String) {
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(value);
+    #[cfg(not(feature = "zeroize"))]
+    value.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_string_debug_is_redacted() {
+        let secret = SecretString::new("hunter2");
+        assert_eq!(format!("{:?}", secret), "[REDACTED]");
+        assert_eq!(format!("{:?}", Some(secret.clone())), "Some([REDACTED])");
+        assert_eq!(secret.expose_secret(), "hunter2");
+    }
+
+    #[test]
+    fn test_wipe_clears_value() {
+        let mut value = String::from("secret");
+        wipe(&mut value);
+        assert!(value.is_empty());
+    }
+}
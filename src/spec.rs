@@ -0,0 +1,458 @@
+//! npm package spec parsing.
+//!
+//! This module classifies package specs the way `npm-package-arg` does, so
+//! that callers can tell registry specs (versions, ranges, tags and aliases)
+//! apart from git, tarball URL and local file specs, and look up the right
+//! registry for the former.
+
+use crate::error::{Error, Result};
+use crate::registry::extract_scope;
+use regex::Regex;
+use std::sync::LazyLock;
+use url::Url;
+
+/// Specs that are URLs (`https:`, `git+ssh:`, `file:`, `github:`, ...).
+static IS_URL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)^(?:git\+)?[a-z]+:").unwrap());
+
+/// scp-style git specs (`git@github.com:user/repo.git`).
+static IS_SCP_GIT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^[^@]+@[^:.]+\.[^:]+:.+$").unwrap());
+
+/// Local path specs (`./x`, `../x`, `~/x`, `/x`, `C:x`).
+static IS_FILESPEC: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:[.]|~[/\\]|[/\\]|[a-zA-Z]:)").unwrap());
+
+/// Tarball file names.
+static IS_FILE_TYPE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)[.](?:tgz|tar\.gz|tar)$").unwrap());
+
+/// GitHub `user/repo` shorthand, with optional `#committish`.
+static GITHUB_SHORTHAND: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[^/@:.\s][^/@:\s]*/[^/@:\s]+(?:#.*)?$").unwrap());
+
+/// Exact semver version, allowing npm's loose leading `=` / `v`.
+static VERSION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[=v\s]*\d+\.\d+\.\d+(?:-[0-9A-Za-z.-]+)?(?:\+[0-9A-Za-z.-]+)?$").unwrap()
+});
+
+/// A single semver comparator, possibly partial (`^1`, `>=1.2`, `1.x`, `*`).
+static COMPARATOR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?:\^|~>?|[<>]=?|=)?v?(?:[xX*]|\d+)(?:\.(?:[xX*]|\d+)(?:\.(?:[xX*]|\d+)(?:-[0-9A-Za-z.-]+)?(?:\+[0-9A-Za-z.-]+)?)?)?$",
+    )
+    .unwrap()
+});
+
+/// Whitespace between a comparator operator and its version.
+static OPERATOR_SPACE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"([<>=~^])\s+").unwrap());
+
+/// Package names, scoped or unscoped.
+static PACKAGE_NAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:@[A-Za-z0-9\-_.~!*'()]+/)?[A-Za-z0-9\-_~!*'()][A-Za-z0-9\-_.~!*'()]*$")
+        .unwrap()
+});
+
+/// Hosts recognized as hosted git providers.
+const GIT_HOSTS: &[&str] = &[
+    "github.com",
+    "gitlab.com",
+    "bitbucket.org",
+    "gist.github.com",
+    "git.sr.ht",
+];
+
+/// Shortcut prefixes for hosted git providers.
+const GIT_SHORTCUTS: &[&str] = &["github:", "gitlab:", "bitbucket:", "gist:", "sourcehut:"];
+
+/// The kind of a package spec, matching `npm-package-arg` types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecType {
+    /// An exact version (`foo@1.2.3`).
+    Version,
+    /// A semver range (`foo@^1.2`, `foo`).
+    Range,
+    /// A dist-tag (`foo@latest`).
+    Tag,
+    /// An alias to another registry package (`foo@npm:bar@1`).
+    Alias,
+    /// A git repository (`github:user/repo`, `git+ssh://...`).
+    Git,
+    /// A remote tarball URL (`https://host/pkg.tgz`).
+    Remote,
+    /// A local tarball (`file:../pkg.tgz`, `./pkg.tgz`).
+    File,
+    /// A local directory (`file:../pkg`, `../pkg`).
+    Directory,
+}
+
+impl SpecType {
+    /// Check whether specs of this type are fetched from a registry.
+    pub fn is_registry(&self) -> bool {
+        matches!(
+            self,
+            SpecType::Version | SpecType::Range | SpecType::Tag | SpecType::Alias
+        )
+    }
+}
+
+/// A parsed npm package spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageSpec {
+    /// The original spec string.
+    pub raw: String,
+    /// Package name, if the spec has one.
+    pub name: Option<String>,
+    /// Scope of the package name (e.g., `@myorg`).
+    pub scope: Option<String>,
+    /// Kind of the spec.
+    pub spec_type: SpecType,
+    /// What to fetch: the version, range or tag for registry specs, the URL
+    /// for git and remote specs, or the path for local specs. Empty for aliases.
+    pub fetch_spec: String,
+    /// Target of an alias spec.
+    pub sub_spec: Option<Box<PackageSpec>>,
+}
+
+impl PackageSpec {
+    /// Check whether this spec is fetched from a registry.
+    pub fn is_registry(&self) -> bool {
+        self.spec_type.is_registry()
+    }
+
+    /// Get the package name to look up in the registry.
+    ///
+    /// For aliases this is the name of the alias target. Returns `None` for
+    /// specs that are not fetched from a registry.
+    pub fn registry_name(&self) -> Option<&str> {
+        match (&self.spec_type, &self.sub_spec) {
+            (SpecType::Alias, Some(sub)) => sub.name.as_deref(),
+            (t, _) if t.is_registry() => self.name.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// A package spec together with the registry it is fetched from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedSpec {
+    /// The parsed spec.
+    pub spec: PackageSpec,
+    /// Registry URL for registry specs; `None` for git, remote and local specs.
+    pub registry: Option<Url>,
+}
+
+/// Parse an npm package spec.
+///
+/// Accepts the same inputs as `npm install`: `name`, `name@range`,
+/// `@scope/name@tag`, `alias@npm:name@range`, `npm:name@range`, git specs
+/// (`github:user/repo`, `user/repo`, `git+https://...`), tarball URLs and
+/// local paths (`file:../x`, `./x.tgz`).
+///
+/// # Examples
+///
+/// ```
+/// use npmrc_config_rs::{parse_spec, SpecType};
+///
+/// let spec = parse_spec("alias@npm:@corp/pkg@^1.2").unwrap();
+/// assert_eq!(spec.spec_type, SpecType::Alias);
+/// assert_eq!(spec.name.as_deref(), Some("alias"));
+/// assert_eq!(spec.registry_name(), Some("@corp/pkg"));
+///
+/// let spec = parse_spec("github:user/repo").unwrap();
+/// assert_eq!(spec.spec_type, SpecType::Git);
+/// ```
+pub fn parse_spec(spec: &str) -> Result<PackageSpec> {
+    let arg = spec.trim();
+
+    // Find where the name ends: the first `@` that isn't the scope marker
+    let name_ends_at = if let Some(rest) = arg.strip_prefix('@') {
+        rest.find('@').map(|i| i + 1)
+    } else {
+        arg.find('@')
+    }
+    .filter(|&i| i > 0);
+    let name_part = name_ends_at.map_or(arg, |i| &arg[..i]);
+
+    if IS_URL.is_match(arg) {
+        resolve(spec, None, arg)
+    } else if IS_SCP_GIT.is_match(arg) {
+        resolve(spec, None, &format!("git+ssh://{}", arg))
+    } else if !name_part.starts_with('@')
+        && (name_part.contains('/') || name_part.contains('\\') || IS_FILE_TYPE.is_match(name_part))
+    {
+        resolve(spec, None, arg)
+    } else if let Some(i) = name_ends_at {
+        resolve(spec, Some(&arg[..i]), &arg[i + 1..])
+    } else if PACKAGE_NAME.is_match(arg) {
+        resolve(spec, Some(arg), "*")
+    } else {
+        resolve(spec, None, arg)
+    }
+}
+
+/// Classify the spec part of a `name@spec` pair.
+fn resolve(raw: &str, name: Option<&str>, spec: &str) -> Result<PackageSpec> {
+    if let Some(name) = name {
+        if !PACKAGE_NAME.is_match(name) {
+            return Err(invalid(raw, format!("invalid package name '{}'", name)));
+        }
+    }
+
+    let lower = spec.to_ascii_lowercase();
+    let build = |spec_type: SpecType, fetch_spec: &str| PackageSpec {
+        raw: raw.to_string(),
+        name: name.map(str::to_string),
+        scope: name.and_then(extract_scope).map(str::to_string),
+        spec_type,
+        fetch_spec: fetch_spec.to_string(),
+        sub_spec: None,
+    };
+
+    if IS_FILESPEC.is_match(spec) || lower.starts_with("file:") {
+        let path = if lower.starts_with("file:") {
+            &spec[5..]
+        } else {
+            spec
+        };
+        let spec_type = if IS_FILE_TYPE.is_match(path) {
+            SpecType::File
+        } else {
+            SpecType::Directory
+        };
+        return Ok(build(spec_type, path));
+    }
+
+    if lower.starts_with("npm:") {
+        let sub = parse_spec(&spec[4..])?;
+        if sub.spec_type == SpecType::Alias {
+            return Err(invalid(raw, "nested aliases are not supported"));
+        }
+        if !sub.is_registry() {
+            return Err(invalid(raw, "aliases only work for registry packages"));
+        }
+        let mut result = build(SpecType::Alias, "");
+        result.sub_spec = Some(Box::new(sub));
+        return Ok(result);
+    }
+
+    if is_hosted_git(spec) {
+        return Ok(build(SpecType::Git, spec));
+    }
+
+    if IS_URL.is_match(spec) {
+        if lower.starts_with("git+") || lower.starts_with("git:") {
+            return Ok(build(SpecType::Git, spec));
+        }
+        if lower.starts_with("http:") || lower.starts_with("https:") {
+            return Ok(build(SpecType::Remote, spec));
+        }
+        return Err(invalid(raw, format!("unsupported URL type '{}'", spec)));
+    }
+
+    if spec.contains('/') || spec.contains('\\') || IS_FILE_TYPE.is_match(spec) {
+        let spec_type = if IS_FILE_TYPE.is_match(spec) {
+            SpecType::File
+        } else {
+            SpecType::Directory
+        };
+        return Ok(build(spec_type, spec));
+    }
+
+    let Some(name) = name else {
+        return Err(invalid(raw, "missing package name"));
+    };
+    let spec = spec.trim();
+    if VERSION.is_match(spec) {
+        Ok(build(SpecType::Version, spec))
+    } else if is_range(spec) {
+        Ok(build(
+            SpecType::Range,
+            if spec.is_empty() { "*" } else { spec },
+        ))
+    } else if is_valid_tag(spec) {
+        Ok(build(SpecType::Tag, spec))
+    } else {
+        Err(invalid(
+            raw,
+            format!("invalid tag name '{}' for '{}'", spec, name),
+        ))
+    }
+}
+
+/// Check whether a spec refers to a hosted git provider.
+fn is_hosted_git(spec: &str) -> bool {
+    let lower = spec.to_ascii_lowercase();
+    if GIT_SHORTCUTS.iter().any(|s| lower.starts_with(s)) {
+        return true;
+    }
+    if GITHUB_SHORTHAND.is_match(spec) {
+        return true;
+    }
+
+    // Repository URLs on known hosts (`https://github.com/user/repo`)
+    let Ok(url) = Url::parse(spec.strip_prefix("git+").unwrap_or(spec)) else {
+        return false;
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    if !GIT_HOSTS.contains(&host.trim_start_matches("www.")) {
+        return false;
+    }
+    let segments: Vec<&str> = url.path().split('/').filter(|s| !s.is_empty()).collect();
+    matches!(segments.as_slice(), [_, _] | [_, _, "tree", ..])
+}
+
+/// Check whether a spec is a semver range.
+fn is_range(spec: &str) -> bool {
+    let spec = OPERATOR_SPACE.replace_all(spec, "$1");
+    spec.split("||").all(|set| {
+        let set = set.trim();
+        if set.is_empty() {
+            return true;
+        }
+        if let Some((from, to)) = set.split_once(" - ") {
+            return COMPARATOR.is_match(from.trim()) && COMPARATOR.is_match(to.trim());
+        }
+        set.split_whitespace().all(|c| COMPARATOR.is_match(c))
+    })
+}
+
+/// Check whether a spec is usable as a dist-tag (it must not need URL encoding).
+fn is_valid_tag(spec: &str) -> bool {
+    !spec.is_empty()
+        && spec
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.!~*'()".contains(c))
+}
+
+fn invalid(spec: &str, message: impl Into<String>) -> Error {
+    Error::InvalidSpec {
+        spec: spec.to_string(),
+        message: message.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bare_name() {
+        let spec = parse_spec("lodash").unwrap();
+        assert_eq!(spec.name.as_deref(), Some("lodash"));
+        assert_eq!(spec.spec_type, SpecType::Range);
+        assert_eq!(spec.fetch_spec, "*");
+        assert_eq!(spec.scope, None);
+    }
+
+    #[test]
+    fn test_parse_registry_specs() {
+        let spec = parse_spec("@corp/pkg@1.2.3").unwrap();
+        assert_eq!(spec.name.as_deref(), Some("@corp/pkg"));
+        assert_eq!(spec.scope.as_deref(), Some("@corp"));
+        assert_eq!(spec.spec_type, SpecType::Version);
+        assert_eq!(spec.fetch_spec, "1.2.3");
+
+        let spec = parse_spec("foo@^1.2 || >= 2.0.0").unwrap();
+        assert_eq!(spec.spec_type, SpecType::Range);
+
+        let spec = parse_spec("foo@1.x").unwrap();
+        assert_eq!(spec.spec_type, SpecType::Range);
+
+        let spec = parse_spec("foo@latest").unwrap();
+        assert_eq!(spec.spec_type, SpecType::Tag);
+        assert_eq!(spec.fetch_spec, "latest");
+
+        let spec = parse_spec("foo@").unwrap();
+        assert_eq!(spec.spec_type, SpecType::Range);
+        assert_eq!(spec.fetch_spec, "*");
+    }
+
+    #[test]
+    fn test_parse_alias() {
+        let spec = parse_spec("alias@npm:@corp/pkg@1").unwrap();
+        assert_eq!(spec.spec_type, SpecType::Alias);
+        assert_eq!(spec.name.as_deref(), Some("alias"));
+        let sub = spec.sub_spec.as_ref().unwrap();
+        assert_eq!(sub.name.as_deref(), Some("@corp/pkg"));
+        assert_eq!(sub.spec_type, SpecType::Range);
+        assert_eq!(spec.registry_name(), Some("@corp/pkg"));
+
+        let spec = parse_spec("npm:@corp/pkg@^1.2").unwrap();
+        assert_eq!(spec.spec_type, SpecType::Alias);
+        assert_eq!(spec.name, None);
+        assert_eq!(spec.registry_name(), Some("@corp/pkg"));
+    }
+
+    #[test]
+    fn test_parse_alias_rejects_non_registry_target() {
+        assert!(matches!(
+            parse_spec("foo@npm:github:user/repo"),
+            Err(Error::InvalidSpec { .. })
+        ));
+        assert!(matches!(
+            parse_spec("foo@npm:bar@npm:baz"),
+            Err(Error::InvalidSpec { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_git() {
+        for raw in [
+            "github:user/repo",
+            "user/repo",
+            "user/repo#v1.0.0",
+            "foo@github:user/repo",
+            "git+ssh://git@github.com/user/repo.git",
+            "git@github.com:user/repo.git",
+            "https://github.com/user/repo",
+            "git://example.com/repo.git",
+        ] {
+            let spec = parse_spec(raw).unwrap();
+            assert_eq!(spec.spec_type, SpecType::Git, "{}", raw);
+            assert!(!spec.is_registry());
+            assert_eq!(spec.registry_name(), None);
+        }
+    }
+
+    #[test]
+    fn test_parse_remote() {
+        let spec = parse_spec("https://host/pkg.tgz").unwrap();
+        assert_eq!(spec.spec_type, SpecType::Remote);
+        assert_eq!(spec.fetch_spec, "https://host/pkg.tgz");
+
+        let spec = parse_spec("foo@https://github.com/user/repo/archive/v1.tar.gz").unwrap();
+        assert_eq!(spec.spec_type, SpecType::Remote);
+        assert_eq!(spec.name.as_deref(), Some("foo"));
+    }
+
+    #[test]
+    fn test_parse_local() {
+        let spec = parse_spec("file:../x").unwrap();
+        assert_eq!(spec.spec_type, SpecType::Directory);
+        assert_eq!(spec.fetch_spec, "../x");
+
+        let spec = parse_spec("file:../x.tgz").unwrap();
+        assert_eq!(spec.spec_type, SpecType::File);
+
+        let spec = parse_spec("./pkg.tar.gz").unwrap();
+        assert_eq!(spec.spec_type, SpecType::File);
+
+        let spec = parse_spec("foo@../libs/foo").unwrap();
+        assert_eq!(spec.spec_type, SpecType::Directory);
+        assert_eq!(spec.name.as_deref(), Some("foo"));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(matches!(
+            parse_spec("foo@not a tag"),
+            Err(Error::InvalidSpec { .. })
+        ));
+        assert!(matches!(
+            parse_spec("foo@ftp://host/x"),
+            Err(Error::InvalidSpec { .. })
+        ));
+    }
+}
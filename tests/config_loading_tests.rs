@@ -44,6 +44,7 @@ fn setup_full_environment(
         skip_project: false,
         skip_user: false,
         skip_global: false,
+        ..Default::default()
     };
 
     (temp, opts)
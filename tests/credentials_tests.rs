@@ -39,7 +39,7 @@ fn test_token_auth_basic() {
 
     match creds {
         Credentials::Token { token, cert } => {
-            assert_eq!(token, "npm_abc123xyz");
+            assert_eq!(token.expose_secret(), "npm_abc123xyz");
             assert!(cert.is_none());
         }
         _ => panic!("Expected Token credentials"),
@@ -66,7 +66,7 @@ fn test_token_auth_custom_registry() {
 
     match creds {
         Credentials::Token { token, .. } => {
-            assert_eq!(token, "corp-token-123");
+            assert_eq!(token.expose_secret(), "corp-token-123");
         }
         _ => panic!("Expected Token credentials"),
     }
@@ -81,7 +81,7 @@ fn test_token_auth_with_port() {
 
     match creds {
         Credentials::Token { token, .. } => {
-            assert_eq!(token, "port-token");
+            assert_eq!(token.expose_secret(), "port-token");
         }
         _ => panic!("Expected Token credentials"),
     }
@@ -96,7 +96,7 @@ fn test_token_auth_with_path() {
 
     match creds {
         Credentials::Token { token, .. } => {
-            assert_eq!(token, "path-token");
+            assert_eq!(token.expose_secret(), "path-token");
         }
         _ => panic!("Expected Token credentials"),
     }
@@ -126,7 +126,7 @@ fn test_basic_auth() {
             cert,
         } => {
             assert_eq!(username, "myuser");
-            assert_eq!(password, "mypassword");
+            assert_eq!(password.expose_secret(), "mypassword");
             assert!(cert.is_none());
         }
         _ => panic!("Expected BasicAuth credentials"),
@@ -151,7 +151,7 @@ fn test_basic_auth_password_with_special_chars() {
             username, password, ..
         } => {
             assert_eq!(username, "admin");
-            assert_eq!(password, "p@ss:word!");
+            assert_eq!(password.expose_secret(), "p@ss:word!");
         }
         _ => panic!("Expected BasicAuth credentials"),
     }
@@ -194,9 +194,9 @@ fn test_legacy_auth() {
             password,
             cert,
         } => {
-            assert_eq!(auth, "dXNlcjpwYXNzd29yZA==");
+            assert_eq!(auth.expose_secret(), "dXNlcjpwYXNzd29yZA==");
             assert_eq!(username, "user");
-            assert_eq!(password, "password");
+            assert_eq!(password.expose_secret(), "password");
             assert!(cert.is_none());
         }
         _ => panic!("Expected LegacyAuth credentials"),
@@ -217,7 +217,7 @@ fn test_legacy_auth_with_colon_in_password() {
             username, password, ..
         } => {
             assert_eq!(username, "user");
-            assert_eq!(password, "pass:word:colon");
+            assert_eq!(password.expose_secret(), "pass:word:colon");
         }
         _ => panic!("Expected LegacyAuth credentials"),
     }
@@ -298,7 +298,7 @@ fn test_token_with_client_cert() {
 
     match creds {
         Credentials::Token { token, cert } => {
-            assert_eq!(token, "secure-token");
+            assert_eq!(token.expose_secret(), "secure-token");
             let cert = cert.expect("Should have cert");
             assert_eq!(cert.certfile.to_str().unwrap(), "/path/to/cert.pem");
             assert_eq!(cert.keyfile.to_str().unwrap(), "/path/to/key.pem");
@@ -441,7 +441,7 @@ fn test_no_credentials_for_unconfigured_registry() {
 #[test]
 fn test_token_helper() {
     let creds = Credentials::Token {
-        token: "my-token".to_string(),
+        token: "my-token".into(),
         cert: None,
     };
     assert_eq!(creds.token(), Some("my-token"));
@@ -453,24 +453,30 @@ fn test_token_helper() {
 fn test_basic_auth_helper() {
     let creds = Credentials::BasicAuth {
         username: "user".to_string(),
-        password: "pass".to_string(),
+        password: "pass".into(),
         cert: None,
     };
     assert!(creds.token().is_none());
     assert_eq!(creds.username_password(), Some(("user", "pass")));
     // "user:pass" base64 = "dXNlcjpwYXNz"
-    assert_eq!(creds.basic_auth_header(), Some("dXNlcjpwYXNz".to_string()));
+    assert_eq!(
+        creds.basic_auth_header().unwrap().expose_secret(),
+        "dXNlcjpwYXNz"
+    );
 }
 
 #[test]
 fn test_legacy_auth_helper() {
     let creds = Credentials::LegacyAuth {
-        auth: "dXNlcjpwYXNz".to_string(),
+        auth: "dXNlcjpwYXNz".into(),
         username: "user".to_string(),
-        password: "pass".to_string(),
+        password: "pass".into(),
         cert: None,
     };
     assert!(creds.token().is_none());
     assert_eq!(creds.username_password(), Some(("user", "pass")));
-    assert_eq!(creds.basic_auth_header(), Some("dXNlcjpwYXNz".to_string()));
+    assert_eq!(
+        creds.basic_auth_header().unwrap().expose_secret(),
+        "dXNlcjpwYXNz"
+    );
 }
@@ -2,7 +2,7 @@
 //!
 //! These tests verify complete workflows combining multiple features.
 
-use npmrc_config_rs::{Credentials, LoadOptions, NpmrcConfig};
+use npmrc_config_rs::{ConfigLayer, Credentials, LoadOptions, NpmrcConfig};
 use std::fs;
 use tempfile::TempDir;
 use url::Url;
@@ -43,6 +43,7 @@ fn setup_test_environment(
         skip_project: false,
         skip_user: false,
         skip_global: false,
+        ..Default::default()
     };
 
     (temp, opts)
@@ -88,7 +89,7 @@ registry = https://registry.npmjs.org/
     let creds = config.credentials_for(&company_registry).unwrap();
     match creds {
         Credentials::Token { token, .. } => {
-            assert_eq!(token, "secret-corp-token-123");
+            assert_eq!(token.expose_secret(), "secret-corp-token-123");
         }
         _ => panic!("Expected token credentials"),
     }
@@ -202,7 +203,7 @@ email = developer@company.com
     let creds = config.credentials_for(&company_reg).unwrap();
     match creds {
         Credentials::Token { token, .. } => {
-            assert_eq!(token, "user-personal-token"); // User overrides global
+            assert_eq!(token.expose_secret(), "user-personal-token"); // User overrides global
         }
         _ => panic!("Expected token"),
     }
@@ -245,7 +246,7 @@ registry = https://secure.company.com/
 
     match creds {
         Credentials::Token { token, cert } => {
-            assert_eq!(token, "bearer-token-123");
+            assert_eq!(token.expose_secret(), "bearer-token-123");
             let cert = cert.expect("Should have cert");
             assert_eq!(cert.certfile.to_str().unwrap(), "/etc/ssl/client.crt");
             assert_eq!(cert.keyfile.to_str().unwrap(), "/etc/ssl/client.key");
@@ -282,6 +283,39 @@ registry = https://registry.npmjs.org/
     assert_eq!(raw_token, Some("")); // Empty due to ${VAR?}
 }
 
+// =============================================================================
+// Complete workflow: CI-generated config injected in memory
+// =============================================================================
+
+#[test]
+fn test_workflow_ci_injected_layer() {
+    let (_temp, opts) = setup_test_environment(
+        None,
+        Some("//npm.corp.com/:_authToken = user-token\n"),
+        Some("@corp:registry = https://npm.corp.com/\n"),
+    );
+
+    let mut config = NpmrcConfig::load_with_options(opts).unwrap();
+    config.push_layer(
+        ConfigLayer::parse(
+            "ci",
+            ConfigLayer::PROJECT + 1,
+            "//npm.corp.com/:_authToken = ci-token\n",
+        )
+        .unwrap(),
+    );
+
+    let registry = config.registry_for("@corp/lib");
+    assert_eq!(registry.as_str(), "https://npm.corp.com/");
+    let creds = config.credentials_for(&registry).unwrap();
+    assert_eq!(creds.token(), Some("ci-token"));
+
+    // Dropping the CI layer falls back to the user token
+    config.remove_layer("ci");
+    let creds = config.credentials_for(&registry).unwrap();
+    assert_eq!(creds.token(), Some("user-token"));
+}
+
 // =============================================================================
 // Error handling: graceful degradation
 // =============================================================================
@@ -301,6 +335,7 @@ fn test_graceful_degradation_missing_files() {
         skip_project: false,
         skip_user: false,
         skip_global: false,
+        ..Default::default()
     })
     .unwrap();
 
@@ -364,3 +399,42 @@ fn test_scenario_monorepo_nested_packages() {
         "https://npm.monorepo.dev/"
     );
 }
+
+#[test]
+fn test_scenario_monorepo_workspace_member() {
+    let temp = TempDir::new().unwrap();
+
+    let root = temp.path().join("monorepo");
+    let foo = root.join("packages").join("foo");
+    let foo_src = foo.join("src");
+
+    fs::create_dir_all(&foo_src).unwrap();
+    fs::write(
+        root.join("package.json"),
+        r#"{"name": "monorepo", "workspaces": ["packages/*"]}"#,
+    )
+    .unwrap();
+    fs::write(
+        root.join(".npmrc"),
+        "@monorepo:registry = https://npm.monorepo.dev/\n",
+    )
+    .unwrap();
+    fs::write(foo.join("package.json"), r#"{"name": "@monorepo/foo"}"#).unwrap();
+
+    // Running inside a workspace member picks up the root's .npmrc
+    let config = NpmrcConfig::load_with_options(LoadOptions {
+        cwd: Some(foo_src),
+        skip_user: true,
+        skip_global: true,
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert_eq!(config.local_prefix, root);
+    assert_eq!(config.workspace_root(), Some(root.as_path()));
+    assert_eq!(config.workspace.as_deref(), Some(foo.as_path()));
+    assert_eq!(
+        config.registry_for("@monorepo/foo").as_str(),
+        "https://npm.monorepo.dev/"
+    );
+}